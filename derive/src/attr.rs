@@ -1,10 +1,37 @@
+use crate::case::RenameRule;
+
 #[derive(Clone, Debug, darling::FromDeriveInput)]
-#[darling(attributes(envir), supports(struct_named))]
+#[darling(
+    attributes(envir),
+    forward_attrs(doc),
+    supports(struct_named, enum_unit, enum_named)
+)]
 pub(crate) struct Container {
+    pub attrs: Vec<syn::Attribute>,
+    #[darling(default)]
+    pub case_insensitive: bool,
+    #[darling(default)]
+    pub deny_unknown: bool,
+    #[darling(default)]
+    pub expand: bool,
     pub prefix: Option<String>,
+    pub rename_all: Option<RenameRule>,
+    pub tag: Option<String>,
 }
 
 impl Container {
+    /**
+     * Converts the field or variant `ident` per `rename_all`, `default` is used without rule.
+     */
+    pub fn rename(&self, ident: &syn::Ident, default: fn(&str) -> String) -> String {
+        let name = syn::ext::IdentExt::unraw(ident).to_string();
+
+        match self.rename_all {
+            Some(rule) => rule.apply(&name),
+            None => default(&name),
+        }
+    }
+
     pub fn envir(&self) -> proc_macro2::TokenStream {
         match (
             proc_macro_crate::crate_name("envir"),
@@ -21,17 +48,38 @@ impl Container {
 }
 
 #[derive(Clone, Default, Debug, darling::FromField)]
-#[darling(attributes(envir))]
+#[darling(attributes(envir), forward_attrs(doc))]
 pub(crate) struct Field {
+    pub attrs: Vec<syn::Attribute>,
+    #[darling(multiple)]
+    pub alias: Vec<String>,
     #[darling(default)]
     pub default: Option<darling::util::Override<String>>,
+    #[darling(multiple)]
+    pub deprecated: Vec<String>,
+    #[darling(default)]
+    pub expand: bool,
+    #[darling(default)]
+    pub export_aliases: bool,
     pub export_with: Option<syn::ExprPath>,
+    pub help: Option<String>,
+    pub key_case: Option<RenameRule>,
+    pub length: Option<Bounds>,
     pub load_with: Option<syn::ExprPath>,
+    #[darling(multiple)]
+    pub load_vars: Vec<String>,
     pub name: Option<String>,
     #[darling(default)]
     pub noprefix: bool,
     #[darling(default)]
+    pub non_empty: bool,
+    #[darling(default)]
     pub nested: bool,
+    pub prefix: Option<String>,
+    pub range: Option<Bounds>,
+    pub regex: Option<String>,
+    #[darling(default)]
+    pub secret: bool,
     #[darling(default)]
     pub skip_export: bool,
     #[darling(default)]
@@ -40,4 +88,145 @@ pub(crate) struct Field {
     pub skip: bool,
     pub skip_export_if: Option<syn::ExprPath>,
     pub separator: Option<char>,
+    pub validate: Option<syn::ExprPath>,
+}
+
+#[derive(Clone, Debug, darling::FromMeta)]
+pub(crate) struct Bounds {
+    pub min: Option<syn::Expr>,
+    pub max: Option<syn::Expr>,
+}
+
+impl Bounds {
+    pub fn rule(&self, name: &str) -> String {
+        let bound = |x: &Option<syn::Expr>| {
+            x.as_ref()
+                .map(|x| quote::ToTokens::to_token_stream(x).to_string())
+                .unwrap_or_default()
+        };
+
+        format!("{name}({}..={})", bound(&self.min), bound(&self.max))
+    }
+
+    pub fn tokens(&self) -> (proc_macro2::TokenStream, proc_macro2::TokenStream) {
+        let bound = |x: &Option<syn::Expr>| match x {
+            Some(x) => quote::quote! { ::std::option::Option::Some(#x) },
+            None => quote::quote! { ::std::option::Option::None },
+        };
+
+        (bound(&self.min), bound(&self.max))
+    }
+}
+
+impl Field {
+    pub fn var(&self, attr: &Container, field: &syn::Field, prefix: &str) -> String {
+        let mut var = self
+            .name
+            .clone()
+            .unwrap_or_else(|| attr.rename(field.ident.as_ref().unwrap(), str::to_uppercase));
+
+        if !self.noprefix {
+            var.insert_str(0, prefix);
+        }
+
+        var
+    }
+
+    /**
+     * Likes `var` but the name of a map field ends with `_*`, it stands for every variable
+     * gathered in the map.
+     */
+    pub fn var_pattern(&self, attr: &Container, field: &syn::Field, prefix: &str) -> String {
+        let var = self.var(attr, field, prefix);
+
+        if crate::is_map(&field.ty) {
+            format!("{var}_*")
+        } else {
+            var
+        }
+    }
+
+    /**
+     * The `alias` then `deprecated` names, with the prefix unless `noprefix`.
+     */
+    pub fn aliases(&self, prefix: &str) -> (Vec<String>, Vec<String>) {
+        (
+            self.prefixed(&self.alias, prefix),
+            self.prefixed(&self.deprecated, prefix),
+        )
+    }
+
+    /**
+     * The variables read by the `load_with` function, with the prefix unless `noprefix`.
+     */
+    pub fn load_vars(&self, prefix: &str) -> Vec<String> {
+        self.prefixed(&self.load_vars, prefix)
+    }
+
+    fn prefixed(&self, names: &[String], prefix: &str) -> Vec<String> {
+        names
+            .iter()
+            .map(|x| {
+                if self.noprefix {
+                    x.clone()
+                } else {
+                    format!("{prefix}{x}")
+                }
+            })
+            .collect()
+    }
+
+    /**
+     * The `envir::RenameRule` converting the variable names of a map field to keys, if any.
+     */
+    pub fn key_case(&self, envir: &proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+        match self.key_case {
+            Some(rule) => {
+                let variant = quote::format_ident!("{rule:?}");
+
+                quote::quote! { ::std::option::Option::Some(#envir::RenameRule::#variant) }
+            }
+            None => quote::quote! { ::std::option::Option::None },
+        }
+    }
+
+    pub fn help(&self) -> Option<String> {
+        self.help.clone().or_else(|| crate::doc(&self.attrs))
+    }
+
+    pub fn is_secret(&self, field: &syn::Field) -> bool {
+        self.secret || crate::is_secret(&field.ty)
+    }
+}
+
+#[derive(Clone, Default, Debug, darling::FromVariant)]
+#[darling(attributes(envir))]
+pub(crate) struct Variant {
+    pub name: Option<String>,
+    pub prefix: Option<String>,
+}
+
+impl Variant {
+    pub fn value(&self, attr: &Container, variant: &syn::Variant) -> String {
+        self.name
+            .clone()
+            .unwrap_or_else(|| attr.rename(&variant.ident, str::to_lowercase))
+    }
+}
+
+impl darling::FromMeta for RenameRule {
+    fn from_string(value: &str) -> darling::Result<Self> {
+        Self::RULES
+            .iter()
+            .find(|(name, _)| *name == value)
+            .map(|(_, rule)| *rule)
+            .ok_or_else(|| {
+                let names = Self::RULES.map(|(name, _)| name);
+
+                darling::Error::custom(format!(
+                    "unknown rename rule `{value}`, expected one of: {}",
+                    names.join(", ")
+                ))
+            })
+    }
 }
@@ -0,0 +1,113 @@
+/*
+ * The case conventions of envir: this crate uses them to rename the fields and variants at
+ * compile time, envir includes them with the `rename_rule` macro to convert the map keys at
+ * runtime. It must only depend on `std`.
+ */
+
+/**
+ * A case convention, used by the `rename_all` and `key_case` attributes.
+ */
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RenameRule {
+    Lower,
+    Upper,
+    Pascal,
+    Camel,
+    Snake,
+    ScreamingSnake,
+    Kebab,
+    ScreamingKebab,
+}
+
+impl RenameRule {
+    pub const RULES: [(&str, Self); 8] = [
+        ("lowercase", Self::Lower),
+        ("UPPERCASE", Self::Upper),
+        ("PascalCase", Self::Pascal),
+        ("camelCase", Self::Camel),
+        ("snake_case", Self::Snake),
+        ("SCREAMING_SNAKE_CASE", Self::ScreamingSnake),
+        ("kebab-case", Self::Kebab),
+        ("SCREAMING-KEBAB-CASE", Self::ScreamingKebab),
+    ];
+
+    #[must_use]
+    pub fn apply(self, name: &str) -> String {
+        let words = words(name);
+        let capitalize = |word: &str| {
+            let mut chars = word.chars();
+
+            chars
+                .next()
+                .map(|x| {
+                    x.to_uppercase()
+                        .chain(chars.flat_map(char::to_lowercase))
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+        let join = |separator: &str, f: fn(&str) -> String| {
+            words
+                .iter()
+                .map(|x| f(x))
+                .collect::<Vec<_>>()
+                .join(separator)
+        };
+
+        match self {
+            Self::Lower => name.to_lowercase(),
+            Self::Upper => name.to_uppercase(),
+            Self::Pascal => words.iter().map(|x| capitalize(x)).collect(),
+            Self::Camel => words
+                .iter()
+                .enumerate()
+                .map(|(i, x)| {
+                    if i == 0 {
+                        x.to_lowercase()
+                    } else {
+                        capitalize(x)
+                    }
+                })
+                .collect(),
+            Self::Snake => join("_", str::to_lowercase),
+            Self::ScreamingSnake => join("_", str::to_uppercase),
+            Self::Kebab => join("-", str::to_lowercase),
+            Self::ScreamingKebab => join("-", str::to_uppercase),
+        }
+    }
+}
+
+/**
+ * Splits a snake case, kebab case or camel case name in words.
+ */
+fn words(name: &str) -> Vec<String> {
+    let chars = name.chars().collect::<Vec<_>>();
+    let mut words = Vec::new();
+    let mut word = String::new();
+
+    for (i, c) in chars.iter().copied().enumerate() {
+        if c == '_' || c == '-' {
+            if !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+            continue;
+        }
+
+        if c.is_uppercase() && !word.is_empty() {
+            let previous = chars[i - 1];
+            let next_lower = chars.get(i + 1).is_some_and(|x| x.is_lowercase());
+
+            if !previous.is_uppercase() || next_lower {
+                words.push(std::mem::take(&mut word));
+            }
+        }
+
+        word.push(c);
+    }
+
+    if !word.is_empty() {
+        words.push(word);
+    }
+
+    words
+}
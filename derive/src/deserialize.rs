@@ -1,13 +1,33 @@
 pub(crate) fn impl_macro(ast: &syn::DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
     use darling::FromDeriveInput;
 
-    let attr = crate::attr::Container::from_derive_input(ast).unwrap();
-    let envir = attr.envir();
+    let attr = crate::attr::Container::from_derive_input(ast)?;
 
-    let fields = match ast.data {
-        syn::Data::Struct(ref s) => &s.fields,
-        _ => return crate::error(ast, "this derive macro only works on structs"),
-    };
+    if attr.deny_unknown && attr.prefix.as_deref().unwrap_or("").is_empty() {
+        return crate::error(
+            ast,
+            "`deny_unknown` requires a `prefix`, every variable of the environment would be unknown otherwise",
+        );
+    }
+
+    match ast.data {
+        syn::Data::Struct(ref s) => impl_struct(ast, &attr, &s.fields),
+        syn::Data::Enum(ref e) => match attr.tag {
+            Some(ref tag) => impl_tagged_enum(ast, &attr, tag, e),
+            None => impl_enum(ast, &attr, e),
+        },
+        syn::Data::Union(_) => {
+            crate::error(ast, "this derive macro only works on structs or enums")
+        }
+    }
+}
+
+fn impl_struct(
+    ast: &syn::DeriveInput,
+    attr: &crate::attr::Container,
+    fields: &syn::Fields,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let envir = attr.envir();
 
     if matches!(fields, syn::Fields::Unnamed(_)) {
         return crate::error(
@@ -16,10 +36,11 @@ pub(crate) fn impl_macro(ast: &syn::DeriveInput) -> syn::Result<proc_macro2::Tok
         );
     }
 
-    let from_body = fields
-        .iter()
-        .map(|x| gen_field(&attr, x))
-        .collect::<Result<Vec<_>, _>>()?;
+    let prefix = attr.prefix.as_deref().unwrap_or("");
+    let from_body = gen_constructor(attr, quote::quote! { Self }, prefix, fields)?;
+    let vars_body = gen_vars(attr, prefix, fields)?;
+    let metadata_body = crate::metadata::gen_fields(attr, prefix, fields)?;
+    let variables = crate::metadata::gen_variables(attr, &metadata_body);
 
     let name = &ast.ident;
     let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
@@ -27,50 +48,504 @@ pub(crate) fn impl_macro(ast: &syn::DeriveInput) -> syn::Result<proc_macro2::Tok
     let de = quote::quote! {
         #[automatically_derived]
         impl #impl_generics #envir::Deserialize for #name #ty_generics #where_clause {
-            fn from(env: &std::collections::HashMap<String, String>) -> #envir::Result<Self> {
-                Ok(Self {
-                    #(#from_body, )*
-                })
+            fn from(env: &::std::collections::HashMap<String, String>) -> #envir::Result<Self> {
+                <Self as #envir::Deserialize>::from_source(env)
+            }
+
+            #[allow(non_snake_case)]
+            fn from_prefixed<__S: #envir::Source + ?Sized>(env: &__S, prefix: &str) -> #envir::Result<Self> {
+                #from_body
+            }
+
+            fn vars(prefix: &str) -> ::std::vec::Vec<String> {
+                let mut vars = ::std::vec::Vec::new();
+
+                #(#vars_body; )*
+
+                vars
+            }
+
+            #variables
+        }
+    };
+
+    Ok(de)
+}
+
+fn impl_tagged_enum(
+    ast: &syn::DeriveInput,
+    attr: &crate::attr::Container,
+    tag: &str,
+    data: &syn::DataEnum,
+) -> syn::Result<proc_macro2::TokenStream> {
+    use darling::FromVariant;
+
+    let envir = attr.envir();
+    let prefix = attr.prefix.as_deref().unwrap_or("");
+    let tag_var = format!("{prefix}{tag}");
+    let mut arms = Vec::new();
+    let mut values = Vec::new();
+    let mut vars_body = Vec::new();
+    let mut metadata_body = Vec::new();
+
+    for variant in &data.variants {
+        let variant_attr = crate::attr::Variant::from_variant(variant)?;
+        let ident = &variant.ident;
+        let mut value = variant_attr.value(attr, variant);
+
+        if attr.case_insensitive {
+            value = value.to_lowercase();
+        }
+
+        let variant_prefix = format!("{prefix}{}", variant_attr.prefix.as_deref().unwrap_or(""));
+
+        let r#gen = match variant.fields {
+            // goes through `gen_constructor` to check the unknown variables
+            syn::Fields::Unit if attr.deny_unknown => gen_constructor(
+                attr,
+                quote::quote! { Self::#ident },
+                &variant_prefix,
+                &variant.fields,
+            )?,
+            syn::Fields::Unit => quote::quote! { ::std::result::Result::Ok(Self::#ident) },
+            syn::Fields::Named(_) => {
+                vars_body.extend(gen_vars(attr, &variant_prefix, &variant.fields)?);
+                metadata_body.extend(crate::metadata::gen_fields(
+                    attr,
+                    &variant_prefix,
+                    &variant.fields,
+                )?);
+
+                gen_constructor(
+                    attr,
+                    quote::quote! { Self::#ident },
+                    &variant_prefix,
+                    &variant.fields,
+                )?
+            }
+            syn::Fields::Unnamed(_) => {
+                return crate::error(
+                    variant,
+                    "this derive macro only works on unit or named field variants",
+                );
+            }
+        };
+
+        arms.push(quote::quote! {
+            #value => #r#gen
+        });
+        values.push(value);
+    }
+
+    let help = gen_help(crate::doc(&attr.attrs));
+    metadata_body.insert(0, crate::metadata::gen_tag(ast, attr, &tag_var, &values));
+    let variables = crate::metadata::gen_variables(attr, &metadata_body);
+
+    let expected = values.join(", ");
+    let input = if attr.case_insensitive {
+        quote::quote! { tag.to_lowercase().as_str() }
+    } else {
+        quote::quote! { tag.as_str() }
+    };
+
+    let name = &ast.ident;
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+
+    let de = quote::quote! {
+        #[automatically_derived]
+        impl #impl_generics #envir::Deserialize for #name #ty_generics #where_clause {
+            fn from(env: &::std::collections::HashMap<String, String>) -> #envir::Result<Self> {
+                <Self as #envir::Deserialize>::from_source(env)
+            }
+
+            #[allow(non_snake_case)]
+            fn from_prefixed<__S: #envir::Source + ?Sized>(env: &__S, prefix: &str) -> #envir::Result<Self> {
+                let tag_var = format!("{prefix}{}", #tag_var);
+                let tag: String = #envir::load_option(env, &tag_var, &tag_var, None, ',', false)?
+                    .ok_or_else(|| #envir::Error::missing(&tag_var, #help))?;
+
+                match #input {
+                    #(#arms, )*
+                    _ => ::std::result::Result::Err(#envir::unknown_variant::<Self>(&tag_var, &tag, #expected)),
+                }
+            }
+
+            fn vars(prefix: &str) -> ::std::vec::Vec<String> {
+                let mut vars = vec![format!("{prefix}{}", #tag_var)];
+
+                #(#vars_body; )*
+
+                vars
             }
+
+            #variables
         }
     };
 
     Ok(de)
 }
 
+fn impl_enum(
+    ast: &syn::DeriveInput,
+    attr: &crate::attr::Container,
+    data: &syn::DataEnum,
+) -> syn::Result<proc_macro2::TokenStream> {
+    use darling::FromVariant;
+
+    let mut arms = Vec::new();
+    let mut values = Vec::new();
+
+    for variant in &data.variants {
+        if !matches!(variant.fields, syn::Fields::Unit) {
+            return crate::error(variant, "this derive macro only works on unit variants");
+        }
+
+        let variant_attr = crate::attr::Variant::from_variant(variant)?;
+        let ident = &variant.ident;
+        let mut value = variant_attr.value(attr, variant);
+
+        if attr.case_insensitive {
+            value = value.to_lowercase();
+        }
+
+        arms.push(quote::quote! {
+            #value => ::std::result::Result::Ok(Self::#ident)
+        });
+        values.push(value);
+    }
+
+    let expected = values.join(", ");
+    let input = if attr.case_insensitive {
+        quote::quote! { s.to_lowercase().as_str() }
+    } else {
+        quote::quote! { s }
+    };
+
+    let name = &ast.ident;
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+
+    let de = quote::quote! {
+        #[automatically_derived]
+        impl #impl_generics ::std::str::FromStr for #name #ty_generics #where_clause {
+            type Err = String;
+
+            fn from_str(s: &str) -> ::std::result::Result<Self, Self::Err> {
+                match #input {
+                    #(#arms, )*
+                    _ => ::std::result::Result::Err(format!("unknown variant '{s}', expected one of: {}", #expected)),
+                }
+            }
+        }
+    };
+
+    Ok(de)
+}
+
+fn gen_vars(
+    attr: &crate::attr::Container,
+    prefix: &str,
+    fields: &syn::Fields,
+) -> syn::Result<Vec<proc_macro2::TokenStream>> {
+    use darling::FromField;
+
+    let envir = attr.envir();
+    let mut vars_body = Vec::new();
+
+    for field in fields {
+        let field_attr = crate::attr::Field::from_field(field)?;
+
+        if field_attr.skip || field_attr.skip_load {
+            continue;
+        }
+
+        if field_attr.load_with.is_some() {
+            for var in field_attr.load_vars(prefix) {
+                vars_body.push(quote::quote! {
+                    vars.push(format!("{prefix}{}", #var))
+                });
+            }
+        } else if field_attr.nested {
+            let nested_prefix = field_attr.prefix.as_deref().unwrap_or("");
+            let ty = crate::extract_type_from_option(&field.ty).unwrap_or(&field.ty);
+
+            vars_body.push(quote::quote! {
+                vars.extend(<#ty as #envir::Deserialize>::vars(&format!("{prefix}{}", #nested_prefix)))
+            });
+        } else {
+            let var = field_attr.var_pattern(attr, field, prefix);
+            let (aliases, deprecated) = field_attr.aliases(prefix);
+
+            vars_body.push(quote::quote! {
+                vars.push(format!("{prefix}{}", #var))
+            });
+            for alias in aliases.iter().chain(&deprecated) {
+                vars_body.push(quote::quote! {
+                    vars.push(format!("{prefix}{}", #alias))
+                });
+            }
+        }
+    }
+
+    Ok(vars_body)
+}
+
+fn gen_help(help: Option<String>) -> proc_macro2::TokenStream {
+    match help {
+        Some(help) => {
+            let help = help.replace('\n', " ");
+            quote::quote! { ::std::option::Option::Some(#help) }
+        }
+        None => quote::quote! { ::std::option::Option::None },
+    }
+}
+
+fn gen_constructor(
+    attr: &crate::attr::Container,
+    path: proc_macro2::TokenStream,
+    prefix: &str,
+    fields: &syn::Fields,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let envir = attr.envir();
+    let mut loads = Vec::new();
+    let mut values = Vec::new();
+
+    for field in fields {
+        let name = &field.ident;
+        let local = quote::format_ident!("__{}", name.as_ref().unwrap());
+        let load = gen_field(attr, prefix, field)?;
+
+        loads.push(quote::quote! {
+            let #local = #envir::check_field(&mut errors, #load)
+        });
+        values.push(quote::quote! {
+            #name: #local.unwrap()
+        });
+    }
+
+    let value = match fields {
+        syn::Fields::Unit => path,
+        _ => quote::quote! { #path { #(#values, )* } },
+    };
+
+    if attr.deny_unknown {
+        let container_prefix = attr.prefix.as_deref().unwrap_or("");
+
+        loads.push(quote::quote! {
+            errors.extend(#envir::check_unknown(
+                env,
+                &format!("{prefix}{}", #container_prefix),
+                &<Self as #envir::Deserialize>::vars(prefix),
+            ))
+        });
+    }
+
+    let r#gen = quote::quote! {{
+        let mut errors = ::std::vec::Vec::new();
+
+        #(#loads; )*
+
+        #envir::check_errors(errors)?;
+
+        ::std::result::Result::Ok(#value)
+    }};
+
+    Ok(r#gen)
+}
+
 fn gen_field(
     attr: &crate::attr::Container,
+    prefix: &str,
     field: &syn::Field,
 ) -> syn::Result<proc_macro2::TokenStream> {
     use darling::FromField;
 
     let field_attr = crate::attr::Field::from_field(field)?;
+    let load = gen_load(attr, prefix, field)?;
+
+    if field_attr.skip || field_attr.skip_load {
+        return Ok(load);
+    }
+
     let envir = attr.envir();
-    let name = &field.ident;
-    let mut var = field_attr
-        .name
-        .clone()
-        .unwrap_or_else(|| field.ident.as_ref().unwrap().to_string().to_uppercase());
+    let var = field_attr.var(attr, field, prefix);
+    let mut checks = Vec::new();
 
-    if !field_attr.noprefix {
-        var.insert_str(0, attr.prefix.as_deref().unwrap_or(""));
+    if let Some(validate) = &field_attr.validate {
+        checks.push(quote::quote! {
+            #envir::validate_with(&var, &value, #validate)?
+        });
     }
 
+    let mut value_checks = Vec::new();
+
+    if field_attr.non_empty {
+        value_checks.push(quote::quote! {
+            #envir::validate_non_empty(&var, v.is_empty())?
+        });
+    }
+
+    if let Some(length) = &field_attr.length {
+        let rule = length.rule("length");
+        let (min, max) = length.tokens();
+
+        value_checks.push(quote::quote! {
+            #envir::validate_length(&var, v.len(), #min, #max, #rule)?
+        });
+    }
+
+    let mut item_checks = Vec::new();
+
+    if let Some(range) = &field_attr.range {
+        let rule = range.rule("range");
+        let (min, max) = range.tokens();
+
+        item_checks.push(quote::quote! {
+            #envir::validate_range(&var, x, #min, #max, #rule)?
+        });
+    }
+
+    if let Some(regex) = &field_attr.regex {
+        check_regex(field, regex)?;
+
+        item_checks.push(quote::quote! {
+            #envir::validate_regex(&var, x, {
+                static REGEX: #envir::RegexValue = #envir::RegexValue::new(#regex);
+                &REGEX
+            })?
+        });
+    }
+
+    if !item_checks.is_empty() {
+        let r#gen = if crate::is_vec(&field.ty) || crate::is_option_vec(&field.ty) {
+            quote::quote! {
+                for x in v.iter() {
+                    #(#item_checks; )*
+                }
+            }
+        } else {
+            quote::quote! {
+                let x = v;
+                #(#item_checks; )*
+            }
+        };
+
+        value_checks.push(r#gen);
+    }
+
+    if !value_checks.is_empty() {
+        let r#gen = if crate::is_option(&field.ty) {
+            quote::quote! {
+                if let ::std::option::Option::Some(v) = &value {
+                    #(#value_checks; )*
+                }
+            }
+        } else {
+            quote::quote! {
+                let v = &value;
+                #(#value_checks; )*
+            }
+        };
+
+        checks.push(r#gen);
+    }
+
+    let ty = &field.ty;
+    let mut r#gen = if checks.is_empty() {
+        load
+    } else {
+        quote::quote! {
+            #load.and_then(|value: #ty| {
+                let var = format!("{prefix}{}", #var);
+                #(#checks; )*
+
+                ::std::result::Result::Ok(value)
+            })
+        }
+    };
+
+    if field_attr.is_secret(field) {
+        let pattern = field_attr.var_pattern(attr, field, prefix);
+
+        r#gen = quote::quote! {{
+            let result = #r#gen;
+            #envir::redact_provenance(&format!("{prefix}{}", #pattern));
+
+            result.map_err(#envir::Error::redact)
+        }};
+    }
+
+    Ok(r#gen)
+}
+
+fn gen_load(
+    attr: &crate::attr::Container,
+    prefix: &str,
+    field: &syn::Field,
+) -> syn::Result<proc_macro2::TokenStream> {
+    use darling::FromField;
+
+    let field_attr = crate::attr::Field::from_field(field)?;
+    let envir = attr.envir();
+    let var = field_attr.var(attr, field, prefix);
+    let var = quote::quote! { format!("{prefix}{}", #var) };
+    let (aliases, deprecated) = field_attr.aliases(prefix);
+    let name = if aliases.is_empty() && deprecated.is_empty() {
+        var.clone()
+    } else {
+        quote::quote! {
+            #envir::resolve_var(
+                env,
+                #var,
+                &[#(format!("{prefix}{}", #aliases)),*],
+                &[#(format!("{prefix}{}", #deprecated)),*],
+            )
+        }
+    };
+
     if field_attr.skip || field_attr.skip_load {
         return Ok(quote::quote! {
-            #name: Default::default()
+            ::std::result::Result::Ok(::std::default::Default::default())
         });
     }
 
     if let Some(load_with) = field_attr.load_with {
         return Ok(quote::quote! {
-            #name: #load_with(&env)?
+            #load_with(&#envir::materialize(env)).map_err(::std::convert::Into::into)
         });
     }
 
     if field_attr.nested {
+        let nested_prefix = field_attr.prefix.as_deref().unwrap_or("");
+
+        if crate::is_option(&field.ty) {
+            return Ok(quote::quote! {
+                #envir::load_nested_option(env, &format!("{prefix}{}", #nested_prefix))
+            });
+        }
+
         return Ok(quote::quote! {
-            #name: #envir::Deserialize::from(env)?
+            #envir::Deserialize::from_prefixed(env, &format!("{prefix}{}", #nested_prefix))
+        });
+    }
+
+    let expand = if field_attr.expand || attr.expand {
+        check_expand(field)?;
+        quote::quote! { #envir::EXPAND }
+    } else {
+        quote::quote! { false }
+    };
+
+    if crate::is_map(&field.ty) {
+        let key_case = field_attr.key_case(&envir);
+
+        return Ok(quote::quote! {
+            #envir::load_map(
+                env,
+                &#var,
+                #key_case,
+                #expand,
+                &<Self as #envir::Deserialize>::vars(prefix),
+            )
         });
     }
 
@@ -81,27 +556,159 @@ fn gen_field(
     };
 
     let separator = field_attr.separator.unwrap_or(',');
+    let help = gen_help(field_attr.help());
 
     if crate::is_option(&field.ty) {
         return Ok(quote::quote! {
-            #name: #envir::#load(env, #var, None, #separator)?
+            #envir::#load(env, &#var, &#name, None, #separator, #expand)
         });
     }
 
     let r#gen = match &field_attr.default {
         None => quote::quote! {
-            #name: #envir::#load(env, #var, None, #separator)?
-                .ok_or(#envir::Error::Missing(#var.to_string()))?
+            #envir::#load(env, &#var, &#name, None, #separator, #expand)
+                .and_then(|x| x.ok_or_else(|| #envir::Error::missing(#var, #help)))
         },
         Some(darling::util::Override::Inherit) => quote::quote! {
-            #name: #envir::#load(env, #var, None, #separator)?
-                .unwrap_or_else(::std::default::Default::default)
-        },
-        Some(darling::util::Override::Explicit(path)) => quote::quote! {
-            #name: #envir::#load(env, #var, ::std::option::Option::Some(#path.to_string()), #separator)?
-                .unwrap()
+            #envir::#load(env, &#var, &#name, None, #separator, #expand)
+                .map(::std::option::Option::unwrap_or_default)
         },
+        Some(darling::util::Override::Explicit(default)) => {
+            let default = gen_default(&envir, field, default)?;
+
+            quote::quote! {
+                #envir::#load(env, &#var, &#name, ::std::option::Option::Some({
+                    static DEFAULT: #envir::DefaultValue = #default;
+                    &DEFAULT
+                }), #separator, #expand)
+                    .map(::std::option::Option::unwrap)
+            }
+        }
     };
 
     Ok(r#gen)
 }
+
+/**
+ * With the `extrapolation` feature, the default values are templates, parsed here.
+ */
+#[cfg(feature = "extrapolation")]
+fn gen_default(
+    envir: &proc_macro2::TokenStream,
+    field: &syn::Field,
+    default: &str,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let template = match crate::template::Template::parse(default) {
+        Ok(template) => gen_template(envir, &template),
+        Err(err) => {
+            return crate::error(
+                field,
+                &format!(
+                    "invalid default template `{default}`, at position {}: {}",
+                    err.position, err.message
+                ),
+            );
+        }
+    };
+
+    Ok(quote::quote! {
+        #envir::DefaultValue::new(#default, #template)
+    })
+}
+
+#[cfg(not(feature = "extrapolation"))]
+fn gen_default(
+    envir: &proc_macro2::TokenStream,
+    _: &syn::Field,
+    default: &str,
+) -> syn::Result<proc_macro2::TokenStream> {
+    Ok(quote::quote! {
+        #envir::DefaultValue::new(#default)
+    })
+}
+
+/**
+ * Builds `template` as a constant `envir::extrapolation::Template`.
+ */
+#[cfg(feature = "extrapolation")]
+fn gen_template(
+    envir: &proc_macro2::TokenStream,
+    template: &crate::template::Template,
+) -> proc_macro2::TokenStream {
+    use crate::template::{Op, Part};
+
+    let parts = template.parts.iter().map(|part| match part {
+        Part::Text(text) => {
+            let text = text.as_ref();
+
+            quote::quote! {
+                #envir::extrapolation::Part::Text(::std::borrow::Cow::Borrowed(#text))
+            }
+        }
+        Part::Var { name, modifier } => {
+            let name = name.as_ref();
+            let modifier = match modifier {
+                Some(modifier) => {
+                    let op = match modifier.op {
+                        Op::Default => quote::quote! { Default },
+                        Op::Error => quote::quote! { Error },
+                        Op::Alternative => quote::quote! { Alternative },
+                    };
+                    let colon = modifier.colon;
+                    let word = gen_template(envir, &modifier.word);
+
+                    quote::quote! {
+                        ::std::option::Option::Some(#envir::extrapolation::Modifier {
+                            op: #envir::extrapolation::Op::#op,
+                            colon: #colon,
+                            word: #word,
+                        })
+                    }
+                }
+                None => quote::quote! { ::std::option::Option::None },
+            };
+
+            quote::quote! {
+                #envir::extrapolation::Part::Var {
+                    name: ::std::borrow::Cow::Borrowed(#name),
+                    modifier: #modifier,
+                }
+            }
+        }
+    });
+
+    quote::quote! {
+        #envir::extrapolation::Template {
+            parts: ::std::borrow::Cow::Borrowed(&[#(#parts),*]),
+        }
+    }
+}
+
+#[cfg(feature = "extrapolation")]
+fn check_expand(_: &syn::Field) -> syn::Result<()> {
+    Ok(())
+}
+
+#[cfg(not(feature = "extrapolation"))]
+fn check_expand(field: &syn::Field) -> syn::Result<()> {
+    crate::error(
+        field,
+        "the `expand` attribute requires the `extrapolation` feature of envir",
+    )
+}
+
+#[cfg(feature = "regex")]
+fn check_regex(field: &syn::Field, regex: &str) -> syn::Result<()> {
+    match regex_syntax::Parser::new().parse(regex) {
+        Ok(_) => Ok(()),
+        Err(err) => crate::error(field, &format!("invalid regex `{regex}`: {err}")),
+    }
+}
+
+#[cfg(not(feature = "regex"))]
+fn check_regex(field: &syn::Field, _: &str) -> syn::Result<()> {
+    crate::error(
+        field,
+        "the `regex` attribute requires the `regex` feature of envir",
+    )
+}
@@ -2,8 +2,12 @@
 #![doc = include_str!("../README.md")]
 
 mod attr;
+mod case;
 mod deserialize;
+mod metadata;
 mod serialize;
+#[cfg(feature = "extrapolation")]
+mod template;
 
 #[proc_macro_derive(Deserialize, attributes(envir))]
 pub fn deserialize_derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
@@ -23,10 +27,63 @@ pub fn serialize_derive(input: proc_macro::TokenStream) -> proc_macro::TokenStre
         .into()
 }
 
+/**
+ * Expands to the source of `RenameRule`, so envir converts the map keys at runtime like this
+ * crate renames the fields.
+ */
+#[doc(hidden)]
+#[proc_macro]
+pub fn rename_rule(_: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    include_str!("case.rs")
+        .parse()
+        .expect("the rename rules should be valid rust")
+}
+
+/**
+ * Expands to the source of the template parser, so envir expands the values at runtime with the
+ * parser checking the default values at compile time.
+ */
+#[cfg(feature = "extrapolation")]
+#[doc(hidden)]
+#[proc_macro]
+pub fn template_parser(_: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    include_str!("template.rs")
+        .parse()
+        .expect("the template parser should be valid rust")
+}
+
 pub(crate) fn error<R>(ast: &dyn quote::ToTokens, message: &str) -> syn::Result<R> {
     Err(syn::Error::new_spanned(ast, message))
 }
 
+pub(crate) fn doc(attrs: &[syn::Attribute]) -> Option<String> {
+    let lines = attrs
+        .iter()
+        .filter(|x| x.path().is_ident("doc"))
+        .filter_map(|x| match &x.meta {
+            syn::Meta::NameValue(syn::MetaNameValue {
+                value:
+                    syn::Expr::Lit(syn::ExprLit {
+                        lit: syn::Lit::Str(s),
+                        ..
+                    }),
+                ..
+            }) => Some(s.value().trim().to_string()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    let doc = lines.join("\n").trim().to_string();
+
+    if doc.is_empty() { None } else { Some(doc) }
+}
+
+pub(crate) fn type_name(ty: &syn::Type) -> String {
+    quote::ToTokens::to_token_stream(ty)
+        .to_string()
+        .replace(' ', "")
+}
+
 pub(crate) fn is_option(ty: &syn::Type) -> bool {
     is_ty(ty, "Option")
 }
@@ -41,6 +98,34 @@ pub(crate) fn is_option_vec(ty: &syn::Type) -> bool {
         .unwrap_or_default()
 }
 
+pub(crate) fn is_map(ty: &syn::Type) -> bool {
+    let syn::Type::Path(typepath) = ty else {
+        return false;
+    };
+
+    typepath
+        .path
+        .segments
+        .last()
+        .is_some_and(|x| x.ident == "HashMap" || x.ident == "BTreeMap")
+}
+
+pub(crate) fn is_secret(ty: &syn::Type) -> bool {
+    let ty = crate::extract_type_from_option(ty).unwrap_or(ty);
+    let ty = crate::extract_type_from_vec(ty).unwrap_or(ty);
+    let ty = crate::extract_type_from_map(ty).unwrap_or(ty);
+
+    let syn::Type::Path(typepath) = ty else {
+        return false;
+    };
+
+    typepath
+        .path
+        .segments
+        .last()
+        .is_some_and(|x| x.ident == "Secret")
+}
+
 pub(crate) fn is_ty(ty: &syn::Type, expected: &str) -> bool {
     let syn::Type::Path(typepath) = ty else {
         return false;
@@ -57,6 +142,46 @@ pub(crate) fn is_ty(ty: &syn::Type, expected: &str) -> bool {
             == Some(expected.to_string())
 }
 
+fn extract_type_from_vec(ty: &syn::Type) -> Option<&syn::Type> {
+    if !crate::is_vec(ty) {
+        return None;
+    }
+
+    let syn::Type::Path(typepath) = ty else {
+        return None;
+    };
+
+    let syn::PathArguments::AngleBracketed(ref params) = typepath.path.segments.last()?.arguments
+    else {
+        return None;
+    };
+
+    match params.args.first()? {
+        syn::GenericArgument::Type(ty) => Some(ty),
+        _ => None,
+    }
+}
+
+fn extract_type_from_map(ty: &syn::Type) -> Option<&syn::Type> {
+    if !crate::is_map(ty) {
+        return None;
+    }
+
+    let syn::Type::Path(typepath) = ty else {
+        return None;
+    };
+
+    let syn::PathArguments::AngleBracketed(ref params) = typepath.path.segments.last()?.arguments
+    else {
+        return None;
+    };
+
+    match params.args.iter().nth(1)? {
+        syn::GenericArgument::Type(ty) => Some(ty),
+        _ => None,
+    }
+}
+
 // https://stackoverflow.com/questions/55271857/how-can-i-get-the-t-from-an-optiont-when-using-syn
 fn extract_type_from_option(ty: &syn::Type) -> Option<&syn::Type> {
     use syn::{GenericArgument, Path, PathArguments, PathSegment};
@@ -0,0 +1,121 @@
+/**
+ * Generates `Deserialize::variables`.
+ */
+pub(crate) fn gen_variables(
+    attr: &crate::attr::Container,
+    metadata_body: &[proc_macro2::TokenStream],
+) -> proc_macro2::TokenStream {
+    let envir = attr.envir();
+
+    quote::quote! {
+        fn variables(prefix: &str) -> ::std::vec::Vec<#envir::Variable> {
+            let mut metadata = ::std::vec::Vec::new();
+
+            #(#metadata_body; )*
+
+            metadata
+        }
+    }
+}
+
+pub(crate) fn gen_tag(
+    ast: &syn::DeriveInput,
+    attr: &crate::attr::Container,
+    tag_var: &str,
+    values: &[String],
+) -> proc_macro2::TokenStream {
+    let envir = attr.envir();
+    let ty = ast.ident.to_string();
+    let doc = crate::doc(&attr.attrs).unwrap_or_else(|| format!("One of: {}", values.join(", ")));
+
+    quote::quote! {
+        metadata.push(#envir::Variable {
+            name: format!("{prefix}{}", #tag_var),
+            ty: #ty,
+            default: ::std::option::Option::None,
+            optional: false,
+            separator: ::std::option::Option::None,
+            doc: ::std::option::Option::Some(#doc),
+            secret: false,
+        })
+    }
+}
+
+pub(crate) fn gen_fields(
+    attr: &crate::attr::Container,
+    prefix: &str,
+    fields: &syn::Fields,
+) -> syn::Result<Vec<proc_macro2::TokenStream>> {
+    use darling::FromField;
+
+    let envir = attr.envir();
+    let mut metadata_body = Vec::new();
+
+    for field in fields {
+        let field_attr = crate::attr::Field::from_field(field)?;
+
+        if field_attr.skip || field_attr.skip_load || field_attr.load_with.is_some() {
+            continue;
+        }
+
+        if field_attr.nested {
+            let nested_prefix = field_attr.prefix.as_deref().unwrap_or("");
+            let ty = crate::extract_type_from_option(&field.ty).unwrap_or(&field.ty);
+
+            let variables = quote::quote! {
+                <#ty as #envir::Deserialize>::variables(&format!("{prefix}{}", #nested_prefix))
+            };
+
+            if crate::is_option(&field.ty) {
+                metadata_body.push(quote::quote! {
+                    metadata.extend(#variables.into_iter().map(|mut x| {
+                        x.optional = true;
+                        x
+                    }))
+                });
+            } else {
+                metadata_body.push(quote::quote! {
+                    metadata.extend(#variables)
+                });
+            }
+
+            continue;
+        }
+
+        let var = field_attr.var_pattern(attr, field, prefix);
+        let ty = crate::type_name(&field.ty);
+        let default = match &field_attr.default {
+            Some(darling::util::Override::Explicit(default)) => {
+                quote::quote! { ::std::option::Option::Some(#default) }
+            }
+            _ => quote::quote! { ::std::option::Option::None },
+        };
+        let optional =
+            crate::is_option(&field.ty) || crate::is_map(&field.ty) || field_attr.default.is_some();
+        let separator = if crate::is_vec(&field.ty) || crate::is_option_vec(&field.ty) {
+            let separator = field_attr.separator.unwrap_or(',');
+            quote::quote! { ::std::option::Option::Some(#separator) }
+        } else {
+            quote::quote! { ::std::option::Option::None }
+        };
+        let doc = match field_attr.help() {
+            Some(doc) => quote::quote! { ::std::option::Option::Some(#doc) },
+            None => quote::quote! { ::std::option::Option::None },
+        };
+        let secret = field_attr.is_secret(field);
+
+        metadata_body.push(quote::quote! {
+            metadata.push(#envir::Variable {
+                name: format!("{prefix}{}", #var),
+                ty: #ty,
+                default: #default,
+                optional: #optional,
+                separator: #separator,
+                doc: #doc,
+                secret: #secret,
+            })
+        });
+    }
+
+    Ok(metadata_body)
+}
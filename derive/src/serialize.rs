@@ -1,13 +1,26 @@
 pub(crate) fn impl_macro(ast: &syn::DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
     use darling::FromDeriveInput;
 
-    let attr = crate::attr::Container::from_derive_input(ast).unwrap();
-    let envir = attr.envir();
+    let attr = crate::attr::Container::from_derive_input(ast)?;
 
-    let fields = match ast.data {
-        syn::Data::Struct(ref s) => &s.fields,
-        _ => return crate::error(ast, "this derive macro only works on structs"),
-    };
+    match ast.data {
+        syn::Data::Struct(ref s) => impl_struct(ast, &attr, &s.fields),
+        syn::Data::Enum(ref e) => match attr.tag {
+            Some(ref tag) => impl_tagged_enum(ast, &attr, tag, e),
+            None => impl_enum(ast, &attr, e),
+        },
+        syn::Data::Union(_) => {
+            crate::error(ast, "this derive macro only works on structs or enums")
+        }
+    }
+}
+
+fn impl_struct(
+    ast: &syn::DeriveInput,
+    attr: &crate::attr::Container,
+    fields: &syn::Fields,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let envir = attr.envir();
 
     if matches!(fields, syn::Fields::Unnamed(_)) {
         return crate::error(
@@ -16,10 +29,16 @@ pub(crate) fn impl_macro(ast: &syn::DeriveInput) -> syn::Result<proc_macro2::Tok
         );
     }
 
+    let prefix = attr.prefix.as_deref().unwrap_or("");
     let export_body = fields
         .iter()
-        .map(|x| gen_field(&attr, x))
+        .map(|x| {
+            let name = &x.ident;
+            gen_field(attr, prefix, x, quote::quote! { self.#name })
+        })
         .collect::<Result<Vec<_>, _>>()?;
+    let secrets_body = gen_vars(attr, prefix, fields, true)?;
+    let vars_body = gen_vars(attr, prefix, fields, false)?;
 
     let name = &ast.ident;
     let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
@@ -28,21 +47,239 @@ pub(crate) fn impl_macro(ast: &syn::DeriveInput) -> syn::Result<proc_macro2::Tok
         #[automatically_derived]
         impl #impl_generics #envir::Serialize for #name #ty_generics #where_clause {
             fn collect(&self) -> ::std::collections::HashMap<String, String> {
+                self.collect_prefixed("")
+            }
+
+            #[allow(non_snake_case)]
+            fn collect_prefixed(&self, prefix: &str) -> ::std::collections::HashMap<String, String> {
                 let mut hash_map = ::std::collections::HashMap::new();
 
                 #(#export_body; )*
 
                 hash_map
             }
+
+            fn secrets(prefix: &str) -> ::std::vec::Vec<String> {
+                let mut vars = ::std::vec::Vec::new();
+
+                #(#secrets_body; )*
+
+                vars
+            }
+
+            fn export_vars(prefix: &str) -> ::std::vec::Vec<String> {
+                let mut vars = ::std::vec::Vec::new();
+
+                #(#vars_body; )*
+
+                vars
+            }
         }
     };
 
     Ok(de)
 }
 
+fn impl_tagged_enum(
+    ast: &syn::DeriveInput,
+    attr: &crate::attr::Container,
+    tag: &str,
+    data: &syn::DataEnum,
+) -> syn::Result<proc_macro2::TokenStream> {
+    use darling::FromVariant;
+
+    let envir = attr.envir();
+    let prefix = attr.prefix.as_deref().unwrap_or("");
+    let tag_var = format!("{prefix}{tag}");
+    let mut arms = Vec::new();
+    let mut secrets_body = Vec::new();
+    let mut vars_body = vec![quote::quote! {
+        vars.push(format!("{prefix}{}", #tag_var))
+    }];
+
+    for variant in &data.variants {
+        let variant_attr = crate::attr::Variant::from_variant(variant)?;
+        let ident = &variant.ident;
+        let value = variant_attr.value(attr, variant);
+        let variant_prefix = format!("{prefix}{}", variant_attr.prefix.as_deref().unwrap_or(""));
+        secrets_body.extend(gen_vars(attr, &variant_prefix, &variant.fields, true)?);
+        vars_body.extend(gen_vars(attr, &variant_prefix, &variant.fields, false)?);
+
+        let mut bindings = Vec::new();
+        let mut export_body = Vec::new();
+
+        for field in &variant.fields {
+            let name = &field.ident;
+            let binding = quote::format_ident!("__{}", name.as_ref().unwrap());
+
+            if let Some(r#gen) =
+                gen_field(attr, &variant_prefix, field, quote::quote! { (*#binding) })?
+            {
+                bindings.push(quote::quote! { #name: #binding });
+                export_body.push(r#gen);
+            }
+        }
+
+        let pattern = match variant.fields {
+            syn::Fields::Unit => quote::quote! { Self::#ident },
+            syn::Fields::Named(_) => quote::quote! { Self::#ident { #(#bindings, )* .. } },
+            syn::Fields::Unnamed(_) => {
+                return crate::error(
+                    variant,
+                    "this derive macro only works on unit or named field variants",
+                );
+            }
+        };
+
+        arms.push(quote::quote! {
+            #pattern => {
+                hash_map.insert(format!("{prefix}{}", #tag_var), #value.to_string());
+                #(#export_body; )*
+            }
+        });
+    }
+
+    let name = &ast.ident;
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+
+    let ser = quote::quote! {
+        #[automatically_derived]
+        impl #impl_generics #envir::Serialize for #name #ty_generics #where_clause {
+            fn collect(&self) -> ::std::collections::HashMap<String, String> {
+                self.collect_prefixed("")
+            }
+
+            #[allow(non_snake_case)]
+            fn collect_prefixed(&self, prefix: &str) -> ::std::collections::HashMap<String, String> {
+                let mut hash_map = ::std::collections::HashMap::new();
+
+                match self {
+                    #(#arms, )*
+                }
+
+                hash_map
+            }
+
+            fn secrets(prefix: &str) -> ::std::vec::Vec<String> {
+                let mut vars = ::std::vec::Vec::new();
+
+                #(#secrets_body; )*
+
+                vars
+            }
+
+            fn export_vars(prefix: &str) -> ::std::vec::Vec<String> {
+                let mut vars = ::std::vec::Vec::new();
+
+                #(#vars_body; )*
+
+                vars
+            }
+        }
+    };
+
+    Ok(ser)
+}
+
+fn impl_enum(
+    ast: &syn::DeriveInput,
+    attr: &crate::attr::Container,
+    data: &syn::DataEnum,
+) -> syn::Result<proc_macro2::TokenStream> {
+    use darling::FromVariant;
+
+    let mut arms = Vec::new();
+
+    for variant in &data.variants {
+        if !matches!(variant.fields, syn::Fields::Unit) {
+            return crate::error(variant, "this derive macro only works on unit variants");
+        }
+
+        let variant_attr = crate::attr::Variant::from_variant(variant)?;
+        let ident = &variant.ident;
+        let value = variant_attr.value(attr, variant);
+
+        arms.push(quote::quote! {
+            Self::#ident => #value
+        });
+    }
+
+    let name = &ast.ident;
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+
+    let ser = quote::quote! {
+        #[automatically_derived]
+        impl #impl_generics ::std::fmt::Display for #name #ty_generics #where_clause {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                let value = match self {
+                    #(#arms, )*
+                };
+
+                f.write_str(value)
+            }
+        }
+    };
+
+    Ok(ser)
+}
+
+fn gen_vars(
+    attr: &crate::attr::Container,
+    prefix: &str,
+    fields: &syn::Fields,
+    secrets: bool,
+) -> syn::Result<Vec<proc_macro2::TokenStream>> {
+    use darling::FromField;
+
+    let envir = attr.envir();
+    let method = if secrets {
+        quote::quote! { secrets }
+    } else {
+        quote::quote! { export_vars }
+    };
+    let mut vars_body = Vec::new();
+
+    for field in fields {
+        let field_attr = crate::attr::Field::from_field(field)?;
+
+        if field_attr.skip || field_attr.skip_export || field_attr.export_with.is_some() {
+            continue;
+        }
+
+        if field_attr.nested {
+            let nested_prefix = field_attr.prefix.as_deref().unwrap_or("");
+            let ty = crate::extract_type_from_option(&field.ty).unwrap_or(&field.ty);
+
+            vars_body.push(quote::quote! {
+                vars.extend(<#ty as #envir::Serialize>::#method(&format!("{prefix}{}", #nested_prefix)))
+            });
+        } else if !secrets || field_attr.is_secret(field) {
+            let var = field_attr.var_pattern(attr, field, prefix);
+
+            vars_body.push(quote::quote! {
+                vars.push(format!("{prefix}{}", #var))
+            });
+
+            if field_attr.export_aliases {
+                let (aliases, deprecated) = field_attr.aliases(prefix);
+
+                for alias in aliases.iter().chain(&deprecated) {
+                    vars_body.push(quote::quote! {
+                        vars.push(format!("{prefix}{}", #alias))
+                    });
+                }
+            }
+        }
+    }
+
+    Ok(vars_body)
+}
+
 fn gen_field(
     attr: &crate::attr::Container,
+    prefix: &str,
     field: &syn::Field,
+    value: proc_macro2::TokenStream,
 ) -> syn::Result<Option<proc_macro2::TokenStream>> {
     use darling::FromField;
 
@@ -53,56 +290,76 @@ fn gen_field(
         return Ok(None);
     }
 
-    let name = &field.ident;
-    let var = format!(
-        "{}{}",
-        attr.prefix.as_deref().unwrap_or(""),
-        field_attr
-            .name
-            .unwrap_or_else(|| field.ident.as_ref().unwrap().to_string().to_uppercase())
-    );
+    let var = field_attr.var(attr, field, prefix);
 
     let separator = field_attr.separator.unwrap_or(',');
+    let nested_prefix = field_attr.prefix.as_deref().unwrap_or("");
+    let to_string = if crate::is_secret(&field.ty) {
+        quote::quote! { expose().to_string() }
+    } else {
+        quote::quote! { to_string() }
+    };
 
     let mut r#gen = if let Some(export_with) = field_attr.export_with {
         return Ok(Some(quote::quote! {
-            hash_map.extend(#export_with(&self.#name));
+            hash_map.extend(#export_with(&#value));
         }));
+    } else if crate::is_map(&field.ty) {
+        let key_case = field_attr.key_case(&envir);
+
+        quote::quote! {
+            for (k, v) in &#value {
+                hash_map.insert(format!("{prefix}{}_{}", #var, #envir::map_suffix(#key_case, k)), v.#to_string);
+            }
+        }
     } else if crate::is_option(&field.ty) && field_attr.nested {
         quote::quote! {
-            if let ::std::option::Option::Some(ref v) = self.#name {
-                hash_map.extend(#envir::Serialize::collect(v));
+            if let ::std::option::Option::Some(ref v) = #value {
+                hash_map.extend(#envir::Serialize::collect_prefixed(v, &format!("{prefix}{}", #nested_prefix)));
             }
         }
     } else if crate::is_option_vec(&field.ty) {
         quote::quote! {
-            if let ::std::option::Option::Some(ref v) = self.#name {
-                hash_map.insert(#var.to_string(), v.iter().map(|x| x.to_string()).collect::<::std::vec::Vec<_>>().join(&#separator.to_string()));
+            if let ::std::option::Option::Some(ref v) = #value {
+                hash_map.insert(format!("{prefix}{}", #var), v.iter().map(|x| x.#to_string).collect::<::std::vec::Vec<_>>().join(&#separator.to_string()));
             }
         }
     } else if crate::is_vec(&field.ty) {
         quote::quote! {
-            hash_map.insert(#var.to_string(), self.#name.iter().map(|x| x.to_string()).collect::<::std::vec::Vec<_>>().join(&#separator.to_string()));
+            hash_map.insert(format!("{prefix}{}", #var), #value.iter().map(|x| x.#to_string).collect::<::std::vec::Vec<_>>().join(&#separator.to_string()));
         }
     } else if crate::is_option(&field.ty) {
         quote::quote! {
-            if let ::std::option::Option::Some(ref v) = self.#name {
-                hash_map.insert(#var.to_string(), v.to_string());
+            if let ::std::option::Option::Some(ref v) = #value {
+                hash_map.insert(format!("{prefix}{}", #var), v.#to_string);
             }
         }
     } else if field_attr.nested {
         quote::quote! {
-            hash_map.extend(#envir::Serialize::collect(&self.#name))
+            hash_map.extend(#envir::Serialize::collect_prefixed(&#value, &format!("{prefix}{}", #nested_prefix)))
         }
     } else {
         quote::quote! {
-            hash_map.insert(#var.to_string(), self.#name.to_string())
+            hash_map.insert(format!("{prefix}{}", #var), #value.#to_string)
         }
     };
 
+    if field_attr.export_aliases && !field_attr.nested && !crate::is_map(&field.ty) {
+        let (aliases, deprecated) = field_attr.aliases(prefix);
+        let aliases = aliases.iter().chain(&deprecated);
+
+        r#gen = quote::quote! {
+            #r#gen;
+
+            if let ::std::option::Option::Some(value) = hash_map.get(&format!("{prefix}{}", #var)).cloned() {
+                #(hash_map.insert(format!("{prefix}{}", #aliases), value.clone()); )*
+            }
+        }
+    }
+
     if let Some(skip_export_if) = field_attr.skip_export_if {
         r#gen = quote::quote! {
-            if !#skip_export_if(&self.#name) {
+            if !#skip_export_if(&#value) {
                 #r#gen;
             }
         }
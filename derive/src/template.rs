@@ -0,0 +1,217 @@
+/*
+ * The template parser of envir: this crate uses it to parse the default values at compile time,
+ * envir includes it with the `template_parser` macro to parse the values to expand at runtime.
+ * It must only depend on `std`.
+ */
+
+use std::borrow::Cow;
+
+/**
+ * A parsed template, like `${HOME:-/root}/.config`.
+ *
+ * Supported syntaxes:
+ *
+ * - `$VAR` and `${VAR}`: the value of `VAR`, an error if it’s unset;
+ * - `${VAR:-fallback}` / `${VAR-fallback}`: `fallback` if `VAR` is unset or empty / unset;
+ * - `${VAR:?message}` / `${VAR?message}`: an error with `message` if `VAR` is unset or empty /
+ *   unset;
+ * - `${VAR:+alt}` / `${VAR+alt}`: `alt` if `VAR` is set and not empty / set, an empty string
+ *   otherwise;
+ * - `$$`: a literal `$`.
+ *
+ * `fallback`, `message` and `alt` are templates too.
+ *
+ * The parts are borrowed for the default values, built as constants by the derive macro.
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub struct Template {
+    pub parts: Cow<'static, [Part]>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Part {
+    Text(Cow<'static, str>),
+    Var {
+        name: Cow<'static, str>,
+        modifier: Option<Modifier>,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Modifier {
+    pub op: Op,
+    pub colon: bool,
+    pub word: Template,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Op {
+    Default,
+    Error,
+    Alternative,
+}
+
+/**
+ * A template syntax error, at the character `position`.
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub struct SyntaxError {
+    pub position: usize,
+    pub message: String,
+}
+
+impl Template {
+    pub fn parse(input: &str) -> Result<Self, SyntaxError> {
+        let mut parser = Parser {
+            chars: input.chars().collect(),
+            pos: 0,
+        };
+
+        parser.template(false)
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek();
+        self.pos += 1;
+        c
+    }
+
+    fn template(&mut self, nested: bool) -> Result<Template, SyntaxError> {
+        let mut parts = Vec::new();
+        let mut text = String::new();
+
+        while let Some(c) = self.peek() {
+            match c {
+                '}' if nested => break,
+                '$' => {
+                    self.pos += 1;
+
+                    match self.peek() {
+                        Some('$') => {
+                            self.pos += 1;
+                            text.push('$');
+                        }
+                        Some('{') => {
+                            self.pos += 1;
+                            Self::flush(&mut parts, &mut text);
+                            parts.push(self.braced()?);
+                        }
+                        Some(c) if c == '_' || c.is_ascii_alphabetic() => {
+                            Self::flush(&mut parts, &mut text);
+                            parts.push(Part::Var {
+                                name: self.name(false).into(),
+                                modifier: None,
+                            });
+                        }
+                        _ => text.push('$'),
+                    }
+                }
+                c => {
+                    self.pos += 1;
+                    text.push(c);
+                }
+            }
+        }
+
+        Self::flush(&mut parts, &mut text);
+
+        Ok(Template {
+            parts: parts.into(),
+        })
+    }
+
+    fn braced(&mut self) -> Result<Part, SyntaxError> {
+        let start = self.pos - 2;
+
+        self.skip_spaces();
+        let name = self.name(true);
+        if name.is_empty() {
+            return Err(self.syntax_error(self.pos, "expected a variable name"));
+        }
+        self.skip_spaces();
+
+        let modifier = match self.peek() {
+            Some('}') | None => None,
+            Some(c @ (':' | '-' | '?' | '+')) => {
+                let colon = c == ':';
+                if colon {
+                    self.pos += 1;
+                }
+
+                let op = match self.next() {
+                    Some('-') => Op::Default,
+                    Some('?') => Op::Error,
+                    Some('+') => Op::Alternative,
+                    _ => return Err(self.syntax_error(self.pos - 1, "expected '-', '?' or '+'")),
+                };
+
+                Some(Modifier {
+                    op,
+                    colon,
+                    word: self.template(true)?,
+                })
+            }
+            Some(c) => {
+                return Err(self.syntax_error(self.pos, &format!("unexpected character '{c}'")));
+            }
+        };
+
+        if self.next() != Some('}') {
+            return Err(self.syntax_error(start, "unclosed '${'"));
+        }
+
+        Ok(Part::Var {
+            name: name.into(),
+            modifier,
+        })
+    }
+
+    /**
+     * Reads a variable name. Like the shell, a bare `$NAME` ends at the first character that
+     * isn’t alphanumeric or `_`, only a `${NAME}` can contain a `.`.
+     */
+    fn name(&mut self, braced: bool) -> String {
+        let mut name = String::new();
+
+        while let Some(c) = self.peek() {
+            if c == '_' || (braced && c == '.') || c.is_ascii_alphanumeric() {
+                name.push(c);
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+
+        name
+    }
+
+    fn skip_spaces(&mut self) {
+        while self.peek() == Some(' ') {
+            self.pos += 1;
+        }
+    }
+
+    fn syntax_error(&self, position: usize, message: &str) -> SyntaxError {
+        SyntaxError {
+            position,
+            message: message.to_string(),
+        }
+    }
+
+    fn flush(parts: &mut Vec<Part>, text: &mut String) {
+        if !text.is_empty() {
+            parts.push(Part::Text(std::mem::take(text).into()));
+        }
+    }
+}
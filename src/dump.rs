@@ -0,0 +1,236 @@
+/**
+ * A variable of a configuration dump.
+ */
+struct Entry {
+    name: String,
+    value: Option<String>,
+    default: Option<String>,
+    origin: crate::Origin,
+    /** The alias the value was read from. */
+    alias: Option<String>,
+    secret: bool,
+}
+
+fn entries<T>(config: &T, provenance: &[crate::Provenance]) -> Vec<Entry>
+where
+    T: crate::Deserialize + crate::Serialize,
+{
+    let mut values = config.collect_redacted();
+    let mut entries = Vec::new();
+
+    for var in T::variables("") {
+        let default = var.default.map(|x| {
+            if var.secret {
+                crate::REDACTED.to_string()
+            } else {
+                x.to_string()
+            }
+        });
+
+        // a map field gives a line per key
+        let mut names = if var.name.ends_with('*') {
+            values
+                .keys()
+                .filter(|x| crate::serde::matches(&var.name, x))
+                .cloned()
+                .collect::<Vec<_>>()
+        } else {
+            Vec::new()
+        };
+        names.sort();
+        if names.is_empty() {
+            names.push(var.name);
+        }
+
+        for name in names {
+            let provenance = provenance.iter().rfind(|x| x.var == name);
+
+            entries.push(Entry {
+                value: values.remove(&name),
+                name,
+                default: default.clone(),
+                origin: provenance.map_or(crate::Origin::Unset, |x| x.origin.clone()),
+                alias: provenance.and_then(|x| x.alias.clone()),
+                secret: var.secret,
+            });
+        }
+    }
+
+    entries
+}
+
+/**
+ * Renders the effective configuration `config` as an aligned table, suitable for a
+ * `--print-config` output: each variable with its value, its default and where its value comes
+ * from, according to `provenance` (see `crate::from_source_with_provenance`).
+ *
+ * Secret values are redacted.
+ */
+#[must_use]
+pub fn dump<T>(config: &T, provenance: &[crate::Provenance]) -> String
+where
+    T: crate::Deserialize + crate::Serialize,
+{
+    let entries = entries(config, provenance);
+    let rows = std::iter::once([
+        "VARIABLE".to_string(),
+        "VALUE".to_string(),
+        "DEFAULT".to_string(),
+        "SOURCE".to_string(),
+    ])
+    .chain(entries.into_iter().map(|x| {
+        [
+            x.name,
+            x.value.unwrap_or_default(),
+            x.default.unwrap_or_default(),
+            match x.alias {
+                Some(alias) => format!("{} ({alias})", x.origin),
+                None => x.origin.to_string(),
+            },
+        ]
+    }))
+    .collect::<Vec<_>>();
+
+    let mut widths = [0; 4];
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let mut dump = String::new();
+
+    for row in rows {
+        let line = row
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{cell:width$}"))
+            .collect::<Vec<_>>()
+            .join("  ");
+
+        dump.push_str(line.trim_end());
+        dump.push('\n');
+    }
+
+    dump
+}
+
+/**
+ * Likes `dump` but renders a JSON array, with one object per variable.
+ */
+#[must_use]
+pub fn dump_json<T>(config: &T, provenance: &[crate::Provenance]) -> String
+where
+    T: crate::Deserialize + crate::Serialize,
+{
+    let objects = entries(config, provenance)
+        .into_iter()
+        .map(|x| {
+            format!(
+                "{{\"name\":{},\"value\":{},\"default\":{},\"source\":{},\"alias\":{},\"secret\":{}}}",
+                json_string(&x.name),
+                x.value.as_deref().map_or("null".to_string(), json_string),
+                x.default.as_deref().map_or("null".to_string(), json_string),
+                json_string(&x.origin.to_string()),
+                x.alias.as_deref().map_or("null".to_string(), json_string),
+                x.secret,
+            )
+        })
+        .collect::<Vec<_>>();
+
+    format!("[{}]", objects.join(","))
+}
+
+fn json_string(s: &str) -> String {
+    let mut json = String::with_capacity(s.len() + 2);
+    json.push('"');
+
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if c.is_control() => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+
+    json.push('"');
+    json
+}
+
+#[cfg(test)]
+mod test {
+    #[derive(crate::Deserialize, crate::Serialize)]
+    #[envir(prefix = "APP_")]
+    struct Config {
+        host: String,
+        #[envir(default = "8080")]
+        port: u16,
+        #[envir(secret)]
+        password: String,
+        user: Option<String>,
+    }
+
+    fn load() -> crate::Result<(Config, Vec<crate::Provenance>)> {
+        let source = crate::Layered::new()
+            .fallback([("APP_HOST", "local\"host"), ("APP_PASSWORD", "hunter2")]);
+
+        crate::from_source_with_provenance(&source)
+    }
+
+    #[test]
+    fn dump() -> crate::Result {
+        let (config, provenance) = load()?;
+
+        assert_eq!(
+            crate::dump(&config, &provenance),
+            "VARIABLE      VALUE       DEFAULT  SOURCE
+APP_HOST      local\"host           source
+APP_PORT      8080        8080     default
+APP_PASSWORD  ***                  source
+APP_USER                           unset
+"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn dump_json() -> crate::Result {
+        let (config, provenance) = load()?;
+
+        assert_eq!(
+            crate::dump_json(&config, &provenance),
+            r#"[{"name":"APP_HOST","value":"local\"host","default":null,"source":"source","alias":null,"secret":false},{"name":"APP_PORT","value":"8080","default":"8080","source":"default","alias":null,"secret":false},{"name":"APP_PASSWORD","value":"***","default":null,"source":"source","alias":null,"secret":true},{"name":"APP_USER","value":null,"default":null,"source":"unset","alias":null,"secret":false}]"#
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn alias() -> crate::Result {
+        #[derive(crate::Deserialize, crate::Serialize)]
+        #[envir(prefix = "APP_")]
+        struct Config {
+            #[envir(alias = "DB_URL")]
+            database_url: String,
+        }
+
+        let source = crate::Layered::new().fallback([("APP_DB_URL", "pg://")]);
+        let (config, provenance) = crate::from_source_with_provenance::<Config, _>(&source)?;
+
+        assert_eq!(provenance[0].var, "APP_DATABASE_URL");
+        assert_eq!(provenance[0].alias.as_deref(), Some("APP_DB_URL"));
+        assert_eq!(
+            crate::dump(&config, &provenance),
+            "VARIABLE          VALUE  DEFAULT  SOURCE
+APP_DATABASE_URL  pg://           source (APP_DB_URL)
+"
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,232 @@
+use std::collections::HashMap;
+use std::ffi::OsString;
+
+/**
+ * An isolated set of environment variables.
+ *
+ * Unlike the crate functions, `Env` never reads or mutates the process environment, except
+ * when explicitly created from it with `Env::from_process`.
+ */
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Env {
+    vars: HashMap<String, OsString>,
+}
+
+impl Env {
+    /**
+     * Creates an empty environment.
+     */
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /**
+     * Creates an environment from a copy of the process environment. Variables with a non
+     * unicode name are ignored.
+     */
+    #[must_use]
+    pub fn from_process() -> Self {
+        std::env::vars_os()
+            .filter_map(|(k, v)| Some((k.into_string().ok()?, v)))
+            .collect()
+    }
+
+    /**
+     * Creates an environment from the *.env* file `path`, without loading it in the process
+     * environment.
+     */
+    #[cfg(feature = "dotenv")]
+    pub fn from_path<P: AsRef<std::path::Path>>(path: P) -> crate::Result<Self> {
+        let mut env = Self::new();
+
+        for item in dotenvy::from_path_iter(path)? {
+            let (key, value) = item?;
+            env.set(&key, value);
+        }
+
+        Ok(env)
+    }
+
+    /**
+     * Retreives all variables as an easy printable form. Variables with a non unicode value
+     * are ignored.
+     */
+    #[must_use]
+    pub fn collect(&self) -> HashMap<String, String> {
+        self.vars
+            .iter()
+            .filter_map(|(k, v)| Some((k.clone(), v.to_str()?.to_string())))
+            .collect()
+    }
+
+    /**
+     * Likes `try_get` but directly parses the variable value in desired `T` type.
+     */
+    pub fn try_parse<T: std::str::FromStr>(&self, key: &str) -> crate::Result<Option<T>>
+    where
+        T::Err: ToString,
+    {
+        let value = match self.try_get(key)? {
+            Some(v) => v
+                .parse::<T>()
+                .map_err(|e| crate::Error::parse::<T, _>(key, e.to_string()))?,
+            None => return Ok(None),
+        };
+
+        Ok(Some(value))
+    }
+
+    /**
+     * Likes `try_parse` but returns a `crate::Error::Missing` if the variable isn’t set.
+     */
+    pub fn parse<T: std::str::FromStr>(&self, key: &str) -> crate::Result<T>
+    where
+        T::Err: ToString,
+    {
+        self.try_parse(key)?
+            .ok_or_else(|| crate::Error::missing(key, None))
+    }
+
+    /**
+     * Gets the variable `key`. This returns a `crate::Error::Unicode` if the variable value
+     * isn’t valid unicode.
+     */
+    pub fn try_get(&self, key: &str) -> crate::Result<Option<String>> {
+        let Some(value) = self.vars.get(key) else {
+            return Ok(None);
+        };
+
+        let value = match value.to_str() {
+            Some(v) => v.to_string(),
+            None => return Err(crate::Error::unicode(key, value.clone())),
+        };
+
+        Ok(Some(value))
+    }
+
+    /**
+     * Likes `try_get` but returns a `crate::Error::Missing` if the variable isn’t set.
+     */
+    pub fn get(&self, key: &str) -> crate::Result<String> {
+        self.try_get(key)?
+            .ok_or_else(|| crate::Error::missing(key, None))
+    }
+
+    /**
+     * Sets the variable `key` to the `value`.
+     */
+    pub fn set<T: ToString>(&mut self, key: &str, value: T) {
+        self.vars.insert(key.to_string(), value.to_string().into());
+    }
+
+    /**
+     * Removes the variable `key` and returns its previous value.
+     */
+    pub fn remove(&mut self, key: &str) -> Option<OsString> {
+        self.vars.remove(key)
+    }
+
+    /**
+     * Returns `true` if the variable `key` is set.
+     */
+    #[must_use]
+    pub fn contains(&self, key: &str) -> bool {
+        self.vars.contains_key(key)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &OsString)> {
+        self.vars.iter()
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.vars.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.vars.is_empty()
+    }
+
+    /**
+     * Adds these variables to the `command` environment. Call `Command::env_clear` before to
+     * run the command only with these variables.
+     */
+    pub fn apply<'c>(
+        &self,
+        command: &'c mut std::process::Command,
+    ) -> &'c mut std::process::Command {
+        command.envs(&self.vars)
+    }
+}
+
+impl<K: Into<String>, V: Into<OsString>> FromIterator<(K, V)> for Env {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        Self {
+            vars: iter
+                .into_iter()
+                .map(|(k, v)| (k.into(), v.into()))
+                .collect(),
+        }
+    }
+}
+
+impl<K: Into<String>, V: Into<OsString>> Extend<(K, V)> for Env {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        self.vars
+            .extend(iter.into_iter().map(|(k, v)| (k.into(), v.into())));
+    }
+}
+
+impl From<HashMap<String, String>> for Env {
+    fn from(value: HashMap<String, String>) -> Self {
+        value.into_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn get() -> crate::Result {
+        let mut env = crate::Env::new();
+        assert!(env.try_get("TEST")?.is_none());
+        assert!(env.get("TEST").is_err());
+
+        env.set("TEST", 1);
+        assert_eq!(env.get("TEST")?, "1");
+        assert_eq!(env.parse::<u8>("TEST")?, 1);
+
+        env.remove("TEST");
+        assert!(env.try_parse::<u8>("TEST")?.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn from_process() -> crate::Result {
+        let env = crate::Env::from_process();
+
+        assert!(!env.is_empty());
+        assert_eq!(env.try_get("PATH")?, crate::try_get("PATH")?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn apply() {
+        let mut env = crate::Env::new();
+        env.set("ENV_APPLY", "value");
+
+        let mut command = std::process::Command::new("env");
+        env.apply(command.env_clear());
+
+        assert_eq!(
+            command.get_envs().collect::<Vec<_>>(),
+            [(
+                std::ffi::OsStr::new("ENV_APPLY"),
+                Some(std::ffi::OsStr::new("value"))
+            )]
+        );
+    }
+}
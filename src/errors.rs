@@ -1,17 +1,50 @@
 pub type Result<T = ()> = std::result::Result<T, Error>;
 
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum Error {
     #[cfg(feature = "dotenv")]
     Dotenv(dotenvy::Error),
+    #[cfg(feature = "extrapolation")]
+    Extrapolation(Extrapolation),
     #[cfg(feature = "logger")]
     Logger(String),
     Parse(Parse),
-    Missing(String),
+    Missing(Missing),
+    Multiple(Vec<Error>),
     Unicode(Unicode),
+    Unknown(Unknown),
+    Validation(Validation),
 }
 
 impl Error {
+    /**
+     * Creates a `Error::Missing` for the variable `key`, with an optional `help` message
+     * describing it. Hand-written `Deserialize::from` and `load_with` functions use it to report
+     * an unset variable:
+     *
+     * ```
+     * let error = envir::Error::missing("APP_PORT", Some("The listening port"));
+     *
+     * assert_eq!(error.to_string(), "Missing 'APP_PORT' environment variable: The listening port");
+     * ```
+     */
+    pub fn missing<K: ToString>(key: K, help: Option<&str>) -> Self {
+        Self::Missing(Missing {
+            key: key.to_string(),
+            help: help.map(ToString::to_string),
+        })
+    }
+
+    #[cfg(feature = "extrapolation")]
+    pub(crate) fn extrapolation(key: &str, template: &str, error: ExtrapolationError) -> Self {
+        Self::Extrapolation(Extrapolation {
+            key: key.to_string(),
+            template: template.to_string(),
+            error,
+        })
+    }
+
     pub(crate) fn parse<T, E: ToString>(key: &str, error: E) -> Self {
         Self::Parse(Parse {
             key: key.to_string(),
@@ -20,12 +53,78 @@ impl Error {
         })
     }
 
+    /**
+     * Hides the variable value from this error.
+     */
+    #[doc(hidden)]
+    pub fn redact(self) -> Self {
+        match self {
+            #[cfg(feature = "extrapolation")]
+            Self::Extrapolation(extrapolation) => {
+                let error = match extrapolation.error {
+                    ExtrapolationError::Syntax { position, .. } => ExtrapolationError::Syntax {
+                        position,
+                        message: crate::REDACTED.to_string(),
+                    },
+                    ExtrapolationError::Unset { var, message } => ExtrapolationError::Unset {
+                        var,
+                        message: message.map(|_| crate::REDACTED.to_string()),
+                    },
+                    error @ ExtrapolationError::Cycle { .. } => error,
+                };
+
+                Self::Extrapolation(Extrapolation {
+                    template: crate::REDACTED.to_string(),
+                    error,
+                    ..extrapolation
+                })
+            }
+            Self::Parse(parse) => Self::Parse(Parse {
+                error: crate::REDACTED.to_string(),
+                ..parse
+            }),
+            Self::Multiple(errors) => {
+                Self::Multiple(errors.into_iter().map(Self::redact).collect())
+            }
+            Self::Unicode(unicode) => Self::Unicode(Unicode {
+                value: crate::REDACTED.into(),
+                ..unicode
+            }),
+            Self::Validation(validation) => Self::Validation(Validation {
+                value: None,
+                ..validation
+            }),
+            #[cfg(feature = "dotenv")]
+            error @ Self::Dotenv(_) => error,
+            #[cfg(feature = "logger")]
+            error @ Self::Logger(_) => error,
+            error @ (Self::Missing(_) | Self::Unknown(_)) => error,
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    pub(crate) fn validation<R: ToString>(key: &str, value: Option<String>, rule: R) -> Self {
+        Self::Validation(Validation {
+            key: key.to_string(),
+            value,
+            rule: rule.to_string(),
+        })
+    }
+
     pub(crate) fn unicode(key: &str, value: std::ffi::OsString) -> Self {
         Self::Unicode(Unicode {
             key: key.to_string(),
             value,
         })
     }
+
+    #[cfg(feature = "serde")]
+    pub(crate) fn unknown(key: &str, suggestion: Option<&str>) -> Self {
+        Self::Unknown(Unknown {
+            key: key.to_string(),
+            suggestion: suggestion.map(ToString::to_string),
+        })
+    }
 }
 
 #[cfg(feature = "dotenv")]
@@ -35,6 +134,63 @@ impl From<dotenvy::Error> for Error {
     }
 }
 
+#[cfg(feature = "extrapolation")]
+#[derive(Debug)]
+pub struct Extrapolation {
+    key: String,
+    template: String,
+    error: ExtrapolationError,
+}
+
+#[cfg(feature = "extrapolation")]
+impl Extrapolation {
+    /**
+     * The reason of the failure.
+     */
+    #[must_use]
+    pub fn error(&self) -> &ExtrapolationError {
+        &self.error
+    }
+}
+
+#[cfg(feature = "extrapolation")]
+#[derive(Clone, Debug, PartialEq)]
+pub enum ExtrapolationError {
+    /** The template is malformed. */
+    Syntax { position: usize, message: String },
+    /** A referenced variable isn’t set (or is empty for `${VAR:?message}`). */
+    Unset {
+        var: String,
+        message: Option<String>,
+    },
+    /** Variables reference each other, `path` starts and ends with the same variable. */
+    Cycle { path: Vec<String> },
+}
+
+#[derive(Debug)]
+pub struct Missing {
+    key: String,
+    help: Option<String>,
+}
+
+impl Missing {
+    /**
+     * The missing variable name.
+     */
+    #[must_use]
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /**
+     * The description of the missing variable.
+     */
+    #[must_use]
+    pub fn help(&self) -> Option<&str> {
+        self.help.as_deref()
+    }
+}
+
 #[derive(Debug)]
 pub struct Parse {
     key: String,
@@ -48,20 +204,105 @@ pub struct Unicode {
     value: std::ffi::OsString,
 }
 
+#[derive(Debug)]
+pub struct Unknown {
+    key: String,
+    suggestion: Option<String>,
+}
+
+impl Unknown {
+    /**
+     * The unknown variable name.
+     */
+    #[must_use]
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /**
+     * The closest known variable name.
+     */
+    #[must_use]
+    pub fn suggestion(&self) -> Option<&str> {
+        self.suggestion.as_deref()
+    }
+}
+
+#[derive(Debug)]
+pub struct Validation {
+    key: String,
+    value: Option<String>,
+    rule: String,
+}
+
 impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let s = match self {
             #[cfg(feature = "dotenv")]
             Self::Dotenv(error) => error.to_string(),
+            #[cfg(feature = "extrapolation")]
+            Self::Extrapolation(Extrapolation {
+                key,
+                template,
+                error,
+            }) => match error {
+                ExtrapolationError::Syntax { position, message } => format!(
+                    "Invalid template '{template}' for '{key}' variable, at position {position}: {message}"
+                ),
+                ExtrapolationError::Unset { var, message: None } => {
+                    format!("Unable to expand '{key}' variable: '{var}' is not set")
+                }
+                ExtrapolationError::Unset {
+                    var,
+                    message: Some(message),
+                } => format!("Unable to expand '{key}' variable: {var}: {message}"),
+                ExtrapolationError::Cycle { path } => format!(
+                    "Unable to expand '{key}' variable: cycle detected {}",
+                    path.join(" -> ")
+                ),
+            },
             #[cfg(feature = "logger")]
             Self::Logger(error) => error.clone(),
             Self::Parse(Parse { key, ty, error }) => {
                 format!("Enable to parse '{key}' variable to '{ty}': {error}")
             }
-            Self::Missing(v) => format!("Missing '{v}' environment variable"),
+            Self::Missing(Missing { key, help: None }) => {
+                format!("Missing '{key}' environment variable")
+            }
+            Self::Missing(Missing {
+                key,
+                help: Some(help),
+            }) => format!("Missing '{key}' environment variable: {help}"),
+            Self::Multiple(errors) => {
+                let mut s = format!("{} configuration errors:", errors.len());
+
+                for error in errors {
+                    s.push_str(&format!("\n  - {error}"));
+                }
+
+                s
+            }
             Self::Unicode(Unicode { key, value }) => {
                 format!("environment variable '{key}' was not valid unicode: {value:?}")
             }
+            Self::Unknown(Unknown {
+                key,
+                suggestion: None,
+            }) => format!("Unknown '{key}' environment variable"),
+            Self::Unknown(Unknown {
+                key,
+                suggestion: Some(suggestion),
+            }) => format!("Unknown '{key}' environment variable, did you mean '{suggestion}'?"),
+            Self::Validation(Validation {
+                key,
+                value: Some(value),
+                rule,
+            }) => format!("Invalid value '{value}' for '{key}' variable: {rule}"),
+            Self::Validation(Validation {
+                key,
+                value: None,
+                rule,
+            }) => format!("Invalid value for '{key}' variable: {rule}"),
         };
 
         write!(f, "{s}")
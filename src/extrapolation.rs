@@ -0,0 +1,302 @@
+use crate::errors::ExtrapolationError;
+
+envir_derive::template_parser!();
+
+impl From<SyntaxError> for ExtrapolationError {
+    fn from(value: SyntaxError) -> Self {
+        Self::Syntax {
+            position: value.position,
+            message: value.message,
+        }
+    }
+}
+
+impl Template {
+    pub(crate) fn expand(
+        &self,
+        lookup: &mut dyn FnMut(&str) -> Result<Option<String>, Expand>,
+    ) -> Result<String, Expand> {
+        let mut output = String::new();
+
+        for part in self.parts.iter() {
+            match part {
+                Part::Text(text) => output.push_str(text),
+                Part::Var {
+                    name,
+                    modifier: None,
+                } => match lookup(name)? {
+                    Some(value) => output.push_str(&value),
+                    None => {
+                        return Err(ExtrapolationError::Unset {
+                            var: name.to_string(),
+                            message: None,
+                        }
+                        .into());
+                    }
+                },
+                Part::Var {
+                    name,
+                    modifier: Some(modifier),
+                } => {
+                    let value = lookup(name)?;
+                    let is_set = match &value {
+                        Some(value) => !modifier.colon || !value.is_empty(),
+                        None => false,
+                    };
+
+                    match (modifier.op, is_set) {
+                        (Op::Default | Op::Error, true) => {
+                            output.push_str(&value.unwrap_or_default());
+                        }
+                        (Op::Default, false) | (Op::Alternative, true) => {
+                            output.push_str(&modifier.word.expand(lookup)?);
+                        }
+                        (Op::Error, false) => {
+                            let message = modifier.word.expand(lookup)?;
+
+                            return Err(ExtrapolationError::Unset {
+                                var: name.to_string(),
+                                message: (!message.is_empty()).then_some(message),
+                            }
+                            .into());
+                        }
+                        (Op::Alternative, false) => (),
+                    }
+                }
+            }
+        }
+
+        Ok(output)
+    }
+}
+
+/**
+ * An expansion error: either an extrapolation error or an error from the variable lookup.
+ */
+#[derive(Debug)]
+pub(crate) enum Expand {
+    Extrapolation(ExtrapolationError),
+    Lookup(crate::Error),
+}
+
+impl From<ExtrapolationError> for Expand {
+    fn from(value: ExtrapolationError) -> Self {
+        Self::Extrapolation(value)
+    }
+}
+
+impl From<crate::Error> for Expand {
+    fn from(value: crate::Error) -> Self {
+        Self::Lookup(value)
+    }
+}
+
+/**
+ * Parses then expands `template`, the value of the `key` variable.
+ *
+ * The values returned by `lookup` are expanded too, recursively. A variable referencing itself,
+ * directly or not, is reported as an `ExtrapolationError::Cycle` error.
+ */
+pub(crate) fn expand(
+    key: &str,
+    template: &str,
+    lookup: &mut dyn FnMut(&str) -> crate::Result<Option<String>>,
+) -> crate::Result<String> {
+    let parsed = Template::parse(template)
+        .map_err(|e| crate::Error::extrapolation(key, template, e.into()))?;
+
+    run(key, template, &parsed, vec![key.to_string()], lookup)
+}
+
+/**
+ * Likes `expand` but for an already parsed `template`, the default value of the unset `key`
+ * variable: the template can refer to `key`.
+ */
+pub(crate) fn expand_default(
+    key: &str,
+    template: &str,
+    parsed: &Template,
+    lookup: &mut dyn FnMut(&str) -> crate::Result<Option<String>>,
+) -> crate::Result<String> {
+    run(key, template, parsed, Vec::new(), lookup)
+}
+
+fn run(
+    key: &str,
+    template: &str,
+    parsed: &Template,
+    stack: Vec<String>,
+    lookup: &mut dyn FnMut(&str) -> crate::Result<Option<String>>,
+) -> crate::Result<String> {
+    let mut resolver = Resolver {
+        lookup,
+        stack,
+        resolved: std::collections::HashMap::new(),
+    };
+
+    resolver
+        .expand_parsed(key, template, parsed)
+        .map_err(|e| match e {
+            Expand::Extrapolation(e) => crate::Error::extrapolation(key, template, e),
+            Expand::Lookup(e) => e,
+        })
+}
+
+struct Resolver<'a> {
+    lookup: &'a mut dyn FnMut(&str) -> crate::Result<Option<String>>,
+    /** The variables being expanded, from the outermost. */
+    stack: Vec<String>,
+    resolved: std::collections::HashMap<String, Option<String>>,
+}
+
+impl Resolver<'_> {
+    fn expand(&mut self, key: &str, template: &str) -> Result<String, Expand> {
+        let parsed = Template::parse(template)
+            .map_err(|e| Expand::Lookup(crate::Error::extrapolation(key, template, e.into())))?;
+
+        self.expand_parsed(key, template, &parsed)
+    }
+
+    fn expand_parsed(
+        &mut self,
+        key: &str,
+        template: &str,
+        parsed: &Template,
+    ) -> Result<String, Expand> {
+        let error = |e| crate::Error::extrapolation(key, template, e);
+
+        parsed
+            .expand(&mut |name| self.resolve(name))
+            .map_err(|e| match e {
+                // a cycle is reported for the outermost variable
+                Expand::Extrapolation(e @ ExtrapolationError::Cycle { .. }) => e.into(),
+                Expand::Extrapolation(e) => error(e).into(),
+                e => e,
+            })
+    }
+
+    fn resolve(&mut self, name: &str) -> Result<Option<String>, Expand> {
+        if let Some(value) = self.resolved.get(name) {
+            return Ok(value.clone());
+        }
+
+        if let Some(start) = self.stack.iter().position(|x| x == name) {
+            let mut path = self.stack[start..].to_vec();
+            path.push(name.to_string());
+
+            return Err(ExtrapolationError::Cycle { path }.into());
+        }
+
+        let value = match (self.lookup)(name)? {
+            Some(value) => {
+                self.stack.push(name.to_string());
+                let value = self.expand(name, &value);
+                self.stack.pop();
+
+                Some(value?)
+            }
+            None => None,
+        };
+
+        self.resolved.insert(name.to_string(), value.clone());
+
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    fn expand(template: &str) -> crate::Result<String> {
+        let env = std::collections::HashMap::from([
+            ("HOME", "/home/user"),
+            ("EMPTY", ""),
+            ("USER", "user"),
+        ]);
+
+        super::expand("TEST", template, &mut |name| {
+            Ok(env.get(name).map(ToString::to_string))
+        })
+    }
+
+    #[test]
+    fn expansion() -> crate::Result {
+        assert_eq!(expand("${HOME}/.config")?, "/home/user/.config");
+        assert_eq!(expand("${ HOME }")?, "/home/user");
+        assert_eq!(expand("$HOME/$USER")?, "/home/user/user");
+        assert_eq!(expand("$USER.log")?, "user.log");
+        assert_eq!(expand("$$HOME $ 5$")?, "$HOME $ 5$");
+        assert_eq!(expand("${MISSING:-/tmp}")?, "/tmp");
+        assert_eq!(expand("${EMPTY:-/tmp}")?, "/tmp");
+        assert_eq!(expand("${EMPTY-/tmp}")?, "");
+        assert_eq!(expand("${MISSING:-${HOME:-/}/tmp}")?, "/home/user/tmp");
+        assert_eq!(expand("${MISSING:-${OTHER:-/}}")?, "/");
+        assert_eq!(expand("${USER:+set}")?, "set");
+        assert_eq!(expand("${EMPTY:+set}")?, "");
+        assert_eq!(expand("${EMPTY+set}")?, "set");
+        assert_eq!(expand("${USER:?unset}")?, "user");
+
+        Ok(())
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            expand("${MISSING}").unwrap_err().to_string(),
+            "Unable to expand 'TEST' variable: 'MISSING' is not set"
+        );
+        assert_eq!(
+            expand("${EMPTY:?must be set for $USER}")
+                .unwrap_err()
+                .to_string(),
+            "Unable to expand 'TEST' variable: EMPTY: must be set for user"
+        );
+        assert_eq!(
+            expand("${HOME").unwrap_err().to_string(),
+            "Invalid template '${HOME' for 'TEST' variable, at position 0: unclosed '${'"
+        );
+        assert_eq!(
+            expand("${}").unwrap_err().to_string(),
+            "Invalid template '${}' for 'TEST' variable, at position 2: expected a variable name"
+        );
+        assert_eq!(
+            expand("${HOME:=/}").unwrap_err().to_string(),
+            "Invalid template '${HOME:=/}' for 'TEST' variable, at position 7: expected '-', '?' or '+'"
+        );
+    }
+
+    #[test]
+    fn recursion() {
+        let env = std::collections::HashMap::from([
+            ("ROOT", "/srv"),
+            ("HOME", "${ROOT}/home"),
+            ("DATA", "$HOME/data:$HOME/cache"),
+            ("A", "${B}"),
+            ("B", "${C:-$A}"),
+            ("INVALID", "${ROOT"),
+        ]);
+        let mut lookup = |name: &str| Ok(env.get(name).map(ToString::to_string));
+
+        assert_eq!(
+            super::expand("TEST", "$DATA", &mut lookup).unwrap(),
+            "/srv/home/data:/srv/home/cache"
+        );
+        assert_eq!(
+            super::expand("A", "${B}", &mut lookup)
+                .unwrap_err()
+                .to_string(),
+            "Unable to expand 'A' variable: cycle detected A -> B -> A"
+        );
+        assert_eq!(
+            super::expand("TEST", "${A}", &mut lookup)
+                .unwrap_err()
+                .to_string(),
+            "Unable to expand 'TEST' variable: cycle detected A -> B -> A"
+        );
+        assert_eq!(
+            super::expand("TEST", "${INVALID}", &mut lookup)
+                .unwrap_err()
+                .to_string(),
+            "Invalid template '${ROOT' for 'INVALID' variable, at position 0: unclosed '${'"
+        );
+    }
+}
@@ -0,0 +1,215 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+/**
+ * A source querying several sources in order: the first layer defining a variable wins.
+ *
+ * Unlike `crate::dotenv`, the *.env* files are parsed without being applied to the process
+ * environment.
+ *
+ * ```no_run
+ * let source = envir::Layered::new().process();
+ * # #[cfg(feature = "dotenv")]
+ * let source = source.dotenv(".env.local")?.dotenv(".env")?;
+ * let source = source.fallback([("PORT", "8080")]);
+ *
+ * let port = envir::Source::get(&source, "PORT")?;
+ * # Ok::<(), envir::Error>(())
+ * ```
+ */
+#[derive(Default)]
+pub struct Layered {
+    layers: Vec<Box<dyn crate::Source>>,
+}
+
+impl Layered {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /**
+     * Adds a layer, with a lower priority than the previous ones.
+     */
+    #[must_use]
+    pub fn layer<S: crate::Source + 'static>(mut self, source: S) -> Self {
+        self.layers.push(Box::new(source));
+        self
+    }
+
+    /**
+     * Adds the process environment as a layer.
+     */
+    #[must_use]
+    pub fn process(self) -> Self {
+        self.layer(crate::ProcessEnv)
+    }
+
+    /**
+     * Adds the *.env* file `path` as a layer. This returns an error if the file doesn’t exist.
+     */
+    #[cfg(feature = "dotenv")]
+    pub fn dotenv<P: AsRef<std::path::Path>>(self, path: P) -> crate::Result<Self> {
+        Ok(self.layer(File::new(path.as_ref())?))
+    }
+
+    /**
+     * Adds a layer with fixed values.
+     */
+    #[must_use]
+    pub fn fallback<K: ToString, V: ToString>(
+        self,
+        vars: impl IntoIterator<Item = (K, V)>,
+    ) -> Self {
+        let vars = vars
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect::<HashMap<_, _>>();
+
+        self.layer(vars)
+    }
+}
+
+impl crate::Source for Layered {
+    fn get(&self, key: &str) -> crate::Result<Option<Cow<'_, str>>> {
+        for layer in &self.layers {
+            if let Some(value) = layer.get(key)? {
+                return Ok(Some(value));
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn contains(&self, key: &str) -> bool {
+        self.layers.iter().any(|x| x.contains(key))
+    }
+
+    fn origin(&self, key: &str) -> crate::Origin {
+        self.layers
+            .iter()
+            .find(|x| x.contains(key))
+            .map_or(crate::Origin::Unset, |x| x.origin(key))
+    }
+
+    /**
+     * Merges the variables of the iterable layers, the layers that can’t be iterated are
+     * ignored.
+     */
+    fn prefixed(&self, prefix: &str) -> Option<Vec<(String, String)>> {
+        let mut vars = HashMap::new();
+        let mut iterable = false;
+
+        for layer in self.layers.iter().rev() {
+            if let Some(layer_vars) = layer.prefixed(prefix) {
+                vars.extend(layer_vars);
+                iterable = true;
+            }
+        }
+
+        iterable.then(|| vars.into_iter().collect())
+    }
+}
+
+/**
+ * A parsed *.env* file, remembering the line of each variable.
+ */
+#[cfg(feature = "dotenv")]
+struct File {
+    path: std::path::PathBuf,
+    env: crate::Env,
+    lines: HashMap<String, usize>,
+}
+
+#[cfg(feature = "dotenv")]
+impl File {
+    fn new(path: &std::path::Path) -> crate::Result<Self> {
+        let env = crate::Env::from_path(path)?;
+        let contents = std::fs::read_to_string(path).map_err(dotenvy::Error::Io)?;
+        let mut lines = HashMap::new();
+
+        for (n, line) in contents.lines().enumerate() {
+            let line = line.trim_start();
+            let line = line.strip_prefix("export ").unwrap_or(line);
+
+            if let Some((key, _)) = line.split_once('=') {
+                let key = key.trim();
+
+                // skips the lines of multiline values
+                if env.contains(key) {
+                    lines.insert(key.to_string(), n + 1);
+                }
+            }
+        }
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            env,
+            lines,
+        })
+    }
+}
+
+#[cfg(feature = "dotenv")]
+impl crate::Source for File {
+    fn get(&self, key: &str) -> crate::Result<Option<Cow<'_, str>>> {
+        crate::Source::get(&self.env, key)
+    }
+
+    fn contains(&self, key: &str) -> bool {
+        self.env.contains(key)
+    }
+
+    fn prefixed(&self, prefix: &str) -> Option<Vec<(String, String)>> {
+        self.env.prefixed(prefix)
+    }
+
+    fn origin(&self, key: &str) -> crate::Origin {
+        crate::Origin::File {
+            path: self.path.clone(),
+            line: self.lines.get(key).copied(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    #[cfg(feature = "dotenv")]
+    fn layers() -> crate::Result {
+        use crate::Source as _;
+
+        let dir = crate::testing::TempDir::new("layered");
+        std::fs::write(dir.join(".env"), "LAYERED_A=dotenv\nLAYERED_B=dotenv").unwrap();
+
+        let _guard = crate::testing::EnvGuard::new(&[
+            ("LAYERED_A", Some("process")),
+            ("LAYERED_B", None),
+            ("LAYERED_C", None),
+        ]);
+
+        let source = crate::Layered::new()
+            .process()
+            .dotenv(dir.join(".env"))?
+            .fallback([("LAYERED_B", "fallback"), ("LAYERED_C", "fallback")]);
+
+        assert_eq!(source.get("LAYERED_A")?.as_deref(), Some("process"));
+        assert_eq!(source.get("LAYERED_B")?.as_deref(), Some("dotenv"));
+        assert_eq!(source.get("LAYERED_C")?.as_deref(), Some("fallback"));
+        assert_eq!(source.get("LAYERED_D")?, None);
+        assert!(crate::try_get("LAYERED_B")?.is_none());
+
+        let mut vars = source.prefixed("LAYERED_").unwrap();
+        vars.sort();
+        assert_eq!(
+            vars,
+            [
+                ("LAYERED_A".to_string(), "process".to_string()),
+                ("LAYERED_B".to_string(), "dotenv".to_string()),
+                ("LAYERED_C".to_string(), "fallback".to_string()),
+            ]
+        );
+
+        Ok(())
+    }
+}
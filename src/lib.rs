@@ -2,16 +2,53 @@
 #![cfg_attr(docsrs, feature(doc_cfg))]
 #![doc = include_str!("../README.md")]
 
+#[cfg(feature = "serde")]
+mod case {
+    envir_derive::rename_rule!();
+}
+#[cfg(feature = "serde")]
+mod dump;
+mod env;
 mod errors;
+#[cfg(feature = "extrapolation")]
+#[doc(hidden)]
+pub mod extrapolation;
+mod layered;
 #[cfg(any(feature = "env_logger", feature = "tracing"))]
 pub mod logger;
 #[cfg(feature = "serde")]
+mod metadata;
+#[cfg(feature = "dotenv")]
+mod profile;
+#[cfg(feature = "serde")]
+mod provenance;
+mod secret;
+#[cfg(feature = "serde")]
 mod serde;
+mod source;
+pub mod testing;
 
+#[cfg(feature = "serde")]
+#[doc(hidden)]
+pub use case::RenameRule;
+#[cfg(feature = "serde")]
+pub use dump::{dump, dump_json};
+#[cfg(feature = "serde")]
+pub use metadata::*;
+#[cfg(feature = "serde")]
+pub use provenance::{Provenance, from_source_with_provenance, redact_provenance};
 #[cfg(feature = "serde")]
 pub use serde::*;
 
+pub use env::Env;
+#[cfg(feature = "extrapolation")]
+pub use errors::ExtrapolationError;
 pub use errors::{Error, Result};
+pub use layered::Layered;
+#[cfg(feature = "dotenv")]
+pub use profile::Dotenv;
+pub use secret::{REDACTED, Secret};
+pub use source::{FromFn, Origin, ProcessEnv, Source, from_fn};
 
 /**
  * Loads the *.env* file and initializes the logger.
@@ -45,6 +82,17 @@ pub fn dotenv() {
     dotenvy::dotenv().ok();
 }
 
+/**
+ * Loads the *.env*, *.env.local*, *.env.{profile}* and *.env.{profile}.local* files, where
+ * profile is read from the `var` environment variable. Returns the loaded files.
+ *
+ * See `Dotenv` for more options.
+ */
+#[cfg(feature = "dotenv")]
+pub fn dotenv_profile(var: &str) -> Result<Vec<std::path::PathBuf>> {
+    Dotenv::new().profile_var(var).load()
+}
+
 /**
  * Loads environment variables from the specified path.
  */
@@ -85,7 +133,7 @@ pub fn parse<T: std::str::FromStr>(key: &str) -> crate::Result<T>
 where
     T::Err: ToString,
 {
-    crate::try_parse(key)?.ok_or_else(|| crate::Error::Missing(key.to_string()))
+    crate::try_parse(key)?.ok_or_else(|| crate::Error::missing(key, None))
 }
 
 /**
@@ -109,11 +157,13 @@ pub fn try_get(key: &str) -> crate::Result<Option<String>> {
  * Likes `try_get` but returns a `crate::Error::Missing` if the variable isn’t set.
  */
 pub fn get(key: &str) -> crate::Result<String> {
-    crate::try_get(key)?.ok_or_else(|| crate::Error::Missing(key.to_string()))
+    crate::try_get(key)?.ok_or_else(|| crate::Error::missing(key, None))
 }
 
 /**
  * Sets the environment variable `key` to the `value`.
+ *
+ * Mutating the process environment isn’t thread safe, consider using an `Env` instead.
  */
 pub fn set<T: ToString>(key: &str, value: T) {
     unsafe {
@@ -121,6 +171,17 @@ pub fn set<T: ToString>(key: &str, value: T) {
     }
 }
 
+/**
+ * Removes the environment variable `key`.
+ *
+ * Mutating the process environment isn’t thread safe, consider using an `Env` instead.
+ */
+pub fn remove(key: &str) {
+    unsafe {
+        std::env::remove_var(key);
+    }
+}
+
 #[cfg(test)]
 mod test {
     #[test]
@@ -137,7 +198,7 @@ mod test {
 
     #[test]
     fn parse() -> crate::Result {
-        crate::set("TEST", 1);
+        let _guard = crate::testing::EnvGuard::new(&[("TEST", Some("1"))]);
         assert_eq!(crate::parse::<u8>("TEST")?, 1u8);
 
         Ok(())
@@ -150,9 +211,19 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn remove() {
+        let mut guard = crate::testing::EnvGuard::lock();
+        guard.set("TEST_REMOVE", "1");
+
+        crate::remove("TEST_REMOVE");
+        assert!(std::env::var("TEST_REMOVE").is_err());
+    }
+
     #[test]
     fn get() -> crate::Result {
-        crate::set("TEST", 1);
+        let mut guard = crate::testing::EnvGuard::lock();
+        guard.set("TEST", "1");
         assert_eq!(crate::get("TEST")?, "1");
 
         Ok(())
@@ -0,0 +1,261 @@
+/**
+ * Describes an environment variable read by a type.
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub struct Variable {
+    /** The variable name, prefix included. It ends with `*` for a map field. */
+    pub name: String,
+    /** The rust type of the field. */
+    pub ty: &'static str,
+    /** The default value, from the `default` attribute. */
+    pub default: Option<&'static str>,
+    /** `true` if this variable may be unset. */
+    pub optional: bool,
+    /** The separator of list items. */
+    pub separator: Option<char>,
+    /** The field documentation. */
+    pub doc: Option<&'static str>,
+    /** `true` if this variable contains a sensitive value. */
+    pub secret: bool,
+}
+
+/**
+ * Renders the variables of `T` as a commented *.env.example* file.
+ *
+ * Required variables are set to their default value (or empty), optional ones are commented.
+ */
+#[must_use]
+pub fn env_example<T: crate::Deserialize>() -> String {
+    let mut example = String::new();
+
+    for var in T::variables("") {
+        if !example.is_empty() {
+            example.push('\n');
+        }
+
+        if let Some(doc) = var.doc {
+            for line in doc.lines() {
+                example.push_str(format!("# {line}").trim_end());
+                example.push('\n');
+            }
+        }
+
+        example.push_str(&format!("# type: {}", var.ty));
+        if let Some(separator) = var.separator {
+            example.push_str(&format!(", separated by '{separator}'"));
+        }
+        example.push('\n');
+
+        if var.optional && var.default.is_none() {
+            example.push_str("# ");
+        }
+
+        example.push_str(&format!(
+            "{}={}\n",
+            var.name,
+            var.default.unwrap_or_default()
+        ));
+    }
+
+    example
+}
+
+/**
+ * Renders the variables of `T` as a markdown table.
+ */
+#[must_use]
+pub fn markdown<T: crate::Deserialize>() -> String {
+    let mut table = "| Variable | Type | Default | Required | Description |\n".to_string();
+    table.push_str("|----------|------|---------|----------|-------------|\n");
+
+    for var in T::variables("") {
+        let default = var.default.map(|x| format!("`{x}`")).unwrap_or_default();
+        let required = if var.optional { "no" } else { "yes" };
+        let doc = var
+            .doc
+            .unwrap_or_default()
+            .replace('|', "\\|")
+            .replace('\n', " ");
+
+        table.push_str(&format!(
+            "| `{}` | `{}` | {default} | {required} | {doc} |\n",
+            var.name, var.ty
+        ));
+    }
+
+    table
+}
+
+/**
+ * Renders the variables of `T` as plain text, suitable for a `--help` output.
+ */
+#[must_use]
+pub fn help<T: crate::Deserialize>() -> String {
+    let metadata = T::variables("");
+    let width = metadata
+        .iter()
+        .map(|x| x.name.len())
+        .max()
+        .unwrap_or_default();
+    let mut help = String::new();
+
+    for var in metadata {
+        let mut line = format!("  {:width$}  {}", var.name, var.ty);
+
+        if let Some(default) = var.default {
+            line.push_str(&format!(" [default: {default}]"));
+        } else if !var.optional {
+            line.push_str(" [required]");
+        }
+
+        help.push_str(line.trim_end());
+        help.push('\n');
+
+        if let Some(doc) = var.doc {
+            for doc_line in doc.lines() {
+                help.push_str(&format!("  {:width$}    {doc_line}", ""));
+                help.push('\n');
+            }
+        }
+    }
+
+    help
+}
+
+#[cfg(test)]
+mod test {
+    /// The application configuration.
+    #[derive(crate::Deserialize)]
+    #[envir(prefix = "APP_")]
+    #[allow(dead_code)]
+    struct Config {
+        /// PostgreSQL connection string
+        database_url: String,
+        #[envir(default = "8080")]
+        port: u16,
+        /// Enabled features
+        #[envir(separator = ';')]
+        features: Option<Vec<String>>,
+        #[envir(nested)]
+        log: Log,
+    }
+
+    #[derive(crate::Deserialize)]
+    #[envir(prefix = "LOG_")]
+    #[allow(dead_code)]
+    struct Log {
+        /// Log level
+        level: Option<String>,
+    }
+
+    #[test]
+    fn metadata() {
+        use crate::Deserialize as _;
+
+        let metadata = Config::variables("");
+
+        assert_eq!(metadata.len(), 4);
+        assert_eq!(
+            metadata[0],
+            crate::Variable {
+                name: "APP_DATABASE_URL".to_string(),
+                ty: "String",
+                default: None,
+                optional: false,
+                separator: None,
+                doc: Some("PostgreSQL connection string"),
+                secret: false,
+            }
+        );
+        assert_eq!(metadata[2].ty, "Option<Vec<String>>");
+        assert_eq!(metadata[2].separator, Some(';'));
+        assert_eq!(metadata[3].name, "LOG_LEVEL");
+    }
+
+    #[test]
+    fn nested() {
+        use crate::Deserialize as _;
+
+        #[derive(crate::Deserialize)]
+        #[allow(dead_code)]
+        struct Test {
+            #[envir(nested)]
+            cache: Option<Cache>,
+            #[envir(nested)]
+            manual: Manual,
+        }
+
+        #[derive(crate::Deserialize)]
+        #[envir(prefix = "CACHE_")]
+        #[allow(dead_code)]
+        struct Cache {
+            url: String,
+        }
+
+        // a hand-written implementation describes no variable
+        struct Manual;
+
+        impl crate::Deserialize for Manual {
+            fn from(_: &std::collections::HashMap<String, String>) -> crate::Result<Self> {
+                Ok(Self)
+            }
+        }
+
+        let metadata = Test::variables("");
+
+        assert_eq!(metadata.len(), 1);
+        assert_eq!(metadata[0].name, "CACHE_URL");
+        assert!(metadata[0].optional);
+    }
+
+    #[test]
+    fn env_example() {
+        assert_eq!(
+            crate::env_example::<Config>(),
+            "# PostgreSQL connection string
+# type: String
+APP_DATABASE_URL=
+
+# type: u16
+APP_PORT=8080
+
+# Enabled features
+# type: Option<Vec<String>>, separated by ';'
+# APP_FEATURES=
+
+# Log level
+# type: Option<String>
+# LOG_LEVEL=
+"
+        );
+    }
+
+    #[test]
+    fn markdown() {
+        assert_eq!(
+            crate::markdown::<Config>(),
+            "| Variable | Type | Default | Required | Description |
+|----------|------|---------|----------|-------------|
+| `APP_DATABASE_URL` | `String` |  | yes | PostgreSQL connection string |
+| `APP_PORT` | `u16` | `8080` | no |  |
+| `APP_FEATURES` | `Option<Vec<String>>` |  | no | Enabled features |
+| `LOG_LEVEL` | `Option<String>` |  | no | Log level |
+"
+        );
+    }
+
+    #[test]
+    fn help() {
+        assert_eq!(
+            crate::help::<Config>(),
+            "  APP_DATABASE_URL  String [required]
+                      PostgreSQL connection string
+  APP_PORT          u16 [default: 8080]
+  APP_FEATURES      Option<Vec<String>>
+                      Enabled features
+  LOG_LEVEL         Option<String>
+                      Log level
+"
+        );
+    }
+}
@@ -0,0 +1,243 @@
+use std::path::{Path, PathBuf};
+
+/**
+ * Loads a cascade of *.env* files, depending of a profile.
+ *
+ * The files are loaded in this order of precedence (the first has the highest priority):
+ *
+ * 1. `.env.{profile}.local`;
+ * 2. `.env.{profile}`;
+ * 3. `.env.local`;
+ * 4. `.env`.
+ *
+ * Like `crate::dotenv`, variables already set in the process environment are never overridden.
+ *
+ * ```no_run
+ * let loaded = envir::Dotenv::new()
+ *     .profile_var("APP_ENV")
+ *     .default_profile("development")
+ *     .load()?;
+ * # Ok::<(), envir::Error>(())
+ * ```
+ */
+#[derive(Clone, Debug)]
+pub struct Dotenv {
+    dir: PathBuf,
+    profile: Option<String>,
+    profile_var: String,
+    default_profile: Option<String>,
+    release: bool,
+}
+
+impl Default for Dotenv {
+    fn default() -> Self {
+        Self {
+            dir: PathBuf::from("."),
+            profile: None,
+            profile_var: "APP_ENV".to_string(),
+            default_profile: None,
+            release: false,
+        }
+    }
+}
+
+impl Dotenv {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /**
+     * Sets the directory containing the *.env* files, the current directory by default.
+     */
+    #[must_use]
+    pub fn dir<P: AsRef<Path>>(mut self, dir: P) -> Self {
+        self.dir = dir.as_ref().to_path_buf();
+        self
+    }
+
+    /**
+     * Uses this profile instead of reading it from the profile variable.
+     */
+    #[must_use]
+    pub fn profile(mut self, profile: &str) -> Self {
+        self.profile = Some(profile.to_string());
+        self
+    }
+
+    /**
+     * Sets the variable containing the profile name, `APP_ENV` by default. This variable is read
+     * from the process environment, then from the `.env.local` and `.env` files.
+     */
+    #[must_use]
+    pub fn profile_var(mut self, var: &str) -> Self {
+        self.profile_var = var.to_string();
+        self
+    }
+
+    /**
+     * Sets the profile used if the profile variable isn’t set.
+     */
+    #[must_use]
+    pub fn default_profile(mut self, profile: &str) -> Self {
+        self.default_profile = Some(profile.to_string());
+        self
+    }
+
+    /**
+     * Also loads the files in release build. By default, like `crate::init`, the files are only
+     * loaded in debug build.
+     */
+    #[must_use]
+    pub fn release(mut self, release: bool) -> Self {
+        self.release = release;
+        self
+    }
+
+    /**
+     * Returns the profile name.
+     */
+    pub fn current_profile(&self) -> crate::Result<Option<String>> {
+        if let Some(profile) = &self.profile {
+            return Ok(Some(profile.clone()));
+        }
+
+        if let Some(profile) = crate::try_get(&self.profile_var)? {
+            return Ok(Some(profile));
+        }
+
+        for filename in [".env.local", ".env"] {
+            let path = self.dir.join(filename);
+
+            if !path.exists() {
+                continue;
+            }
+
+            for item in dotenvy::from_path_iter(path)? {
+                let (key, value) = item?;
+
+                if key == self.profile_var {
+                    return Ok(Some(value));
+                }
+            }
+        }
+
+        Ok(self.default_profile.clone())
+    }
+
+    /**
+     * Lists the candidate files, from the highest priority to the lowest.
+     */
+    pub fn files(&self) -> crate::Result<Vec<PathBuf>> {
+        let mut filenames = Vec::new();
+        let profile = self.current_profile()?;
+
+        if let Some(profile) = &profile {
+            filenames.push(format!(".env.{profile}.local"));
+            filenames.push(format!(".env.{profile}"));
+        }
+        filenames.push(".env.local".to_string());
+        filenames.push(".env".to_string());
+
+        let files = filenames.iter().map(|x| self.dir.join(x)).collect();
+
+        Ok(files)
+    }
+
+    /**
+     * Loads the existing files and returns their paths, from the highest priority to the
+     * lowest.
+     */
+    pub fn load(&self) -> crate::Result<Vec<PathBuf>> {
+        if !self.release && !cfg!(debug_assertions) {
+            return Ok(Vec::new());
+        }
+
+        let mut loaded = Vec::new();
+
+        for path in self.files()? {
+            if path.exists() {
+                dotenvy::from_path(&path)?;
+                loaded.push(path);
+            }
+        }
+
+        Ok(loaded)
+    }
+
+    /**
+     * Likes `load` but returns the existing files as layers of a `crate::Layered` source, after
+     * the process environment, instead of applying them.
+     */
+    pub fn source(&self) -> crate::Result<crate::Layered> {
+        let mut source = crate::Layered::new().process();
+
+        if !self.release && !cfg!(debug_assertions) {
+            return Ok(source);
+        }
+
+        for path in self.files()? {
+            if path.exists() {
+                source = source.dotenv(path)?;
+            }
+        }
+
+        Ok(source)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn load() -> crate::Result {
+        let dir = crate::testing::TempDir::new("profile");
+        std::fs::write(
+            dir.join(".env"),
+            "PROFILE_A=env\nPROFILE_B=env\nPROFILE_C=env\nPROFILE_D=env\nPROFILE_ENV=test",
+        )
+        .unwrap();
+        std::fs::write(dir.join(".env.local"), "PROFILE_B=local\nPROFILE_D=local").unwrap();
+        std::fs::write(dir.join(".env.test"), "PROFILE_B=test\nPROFILE_C=test").unwrap();
+        std::fs::write(dir.join(".env.test.local"), "PROFILE_C=test.local").unwrap();
+
+        let _guard = crate::testing::EnvGuard::new(&[
+            ("PROFILE_A", None::<&str>),
+            ("PROFILE_B", None),
+            ("PROFILE_C", None),
+            ("PROFILE_D", None),
+            ("PROFILE_ENV", None),
+        ]);
+
+        let dotenv = crate::Dotenv::new()
+            .dir(&*dir)
+            .profile_var("PROFILE_ENV")
+            .release(true);
+        assert_eq!(dotenv.current_profile()?, Some("test".to_string()));
+
+        let loaded = dotenv.load()?;
+        assert_eq!(
+            loaded,
+            [
+                dir.join(".env.test.local"),
+                dir.join(".env.test"),
+                dir.join(".env.local"),
+                dir.join(".env"),
+            ]
+        );
+
+        assert_eq!(crate::get("PROFILE_A")?, "env");
+        assert_eq!(crate::get("PROFILE_B")?, "test");
+        assert_eq!(crate::get("PROFILE_C")?, "test.local");
+        assert_eq!(crate::get("PROFILE_D")?, "local");
+
+        let _guard = crate::testing::EnvGuard::new(&[("PROFILE_C", None::<&str>)]);
+        let source = dotenv.source()?;
+        assert_eq!(
+            crate::Source::get(&source, "PROFILE_C")?.as_deref(),
+            Some("test.local")
+        );
+        assert!(crate::try_get("PROFILE_C")?.is_none());
+
+        Ok(())
+    }
+}
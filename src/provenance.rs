@@ -0,0 +1,265 @@
+use std::cell::RefCell;
+
+/**
+ * Where the value of a loaded variable comes from.
+ */
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Provenance {
+    pub var: String,
+    pub origin: crate::Origin,
+    /** The raw value, if it was expanded from a template, see the `extrapolation` feature. */
+    pub template: Option<String>,
+    /** The `alias` or `deprecated` name the value was read from, instead of `var`. */
+    pub alias: Option<String>,
+}
+
+impl Provenance {
+    /**
+     * Checks if the `default` attribute of the field was used.
+     */
+    #[must_use]
+    pub fn is_default(&self) -> bool {
+        matches!(self.origin, crate::Origin::Default { .. })
+    }
+
+    /**
+     * Checks if the value was expanded from `template`.
+     */
+    #[must_use]
+    pub fn is_expanded(&self) -> bool {
+        self.template.is_some()
+    }
+}
+
+thread_local! {
+    static RECORDER: RefCell<Option<Vec<Provenance>>> = const { RefCell::new(None) };
+}
+
+/**
+ * Likes `crate::from_source` but also returns the provenance of each variable read, in loading
+ * order. Variables loaded by a `load_with` function aren’t recorded.
+ */
+pub fn from_source_with_provenance<T, S>(source: &S) -> crate::Result<(T, Vec<Provenance>)>
+where
+    T: crate::Deserialize,
+    S: crate::Source + ?Sized,
+{
+    let recording = Recording {
+        previous: RECORDER.replace(Some(Vec::new())),
+    };
+    let result = T::from_source(source);
+    let provenance = RECORDER.take().unwrap_or_default();
+    drop(recording);
+
+    result.map(|x| (x, provenance))
+}
+
+/**
+ * Restores the previous recorder on drop, even if the loading panics.
+ */
+struct Recording {
+    previous: Option<Vec<Provenance>>,
+}
+
+impl Drop for Recording {
+    fn drop(&mut self) {
+        RECORDER.set(self.previous.take());
+    }
+}
+
+/**
+ * Records the provenance of `var`, if called from `from_source_with_provenance`.
+ */
+pub(crate) fn record(
+    var: &str,
+    origin: impl FnOnce() -> crate::Origin,
+    template: Option<&str>,
+    alias: Option<&str>,
+) {
+    RECORDER.with_borrow_mut(|recorder| {
+        if let Some(recorder) = recorder {
+            recorder.push(Provenance {
+                var: var.to_string(),
+                origin: origin(),
+                template: template.map(ToString::to_string),
+                alias: alias.map(ToString::to_string),
+            });
+        }
+    });
+}
+
+/**
+ * Hides the template and the default value recorded for the secret variables matching
+ * `pattern`.
+ */
+#[doc(hidden)]
+pub fn redact_provenance(pattern: &str) {
+    RECORDER.with_borrow_mut(|recorder| {
+        for provenance in recorder.iter_mut().flatten() {
+            if !crate::serde::matches(pattern, &provenance.var) {
+                continue;
+            }
+
+            if provenance.template.is_some() {
+                provenance.template = Some(crate::REDACTED.to_string());
+            }
+            if let crate::Origin::Default { value } = &mut provenance.origin {
+                *value = crate::REDACTED.to_string();
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn panic() {
+        #[derive(Debug, crate::Deserialize)]
+        struct Test {
+            #[envir(load_with = "load")]
+            _value: String,
+        }
+
+        fn load(_: &std::collections::HashMap<String, String>) -> crate::Result<String> {
+            panic!("load failed");
+        }
+
+        let result = std::panic::catch_unwind(|| {
+            crate::from_source_with_provenance::<Test, _>(&std::collections::HashMap::new())
+        });
+
+        assert!(result.is_err());
+        assert!(super::RECORDER.with_borrow(Option::is_none));
+    }
+
+    #[test]
+    fn provenance() -> crate::Result {
+        #[derive(Debug, crate::Deserialize)]
+        #[envir(prefix = "APP_")]
+        struct Test {
+            host: String,
+            #[envir(default = "8080")]
+            port: u16,
+            user: Option<String>,
+            #[envir(secret, default = "hunter2")]
+            password: String,
+        }
+
+        let source = crate::Layered::new()
+            .fallback([("APP_HOST", "localhost")])
+            .layer(crate::from_fn(|_| None));
+
+        let (test, provenance) = crate::from_source_with_provenance::<Test, _>(&source)?;
+
+        assert_eq!(test.host, "localhost");
+        assert_eq!(test.port, 8080);
+        assert_eq!(test.user, None);
+        assert_eq!(test.password, "hunter2");
+        assert_eq!(
+            provenance,
+            [
+                crate::Provenance {
+                    var: "APP_HOST".to_string(),
+                    origin: crate::Origin::Source,
+                    template: None,
+                    alias: None,
+                },
+                crate::Provenance {
+                    var: "APP_PORT".to_string(),
+                    origin: crate::Origin::Default {
+                        value: "8080".to_string(),
+                    },
+                    template: None,
+                    alias: None,
+                },
+                crate::Provenance {
+                    var: "APP_USER".to_string(),
+                    origin: crate::Origin::Unset,
+                    template: None,
+                    alias: None,
+                },
+                crate::Provenance {
+                    var: "APP_PASSWORD".to_string(),
+                    origin: crate::Origin::Default {
+                        value: crate::REDACTED.to_string(),
+                    },
+                    template: None,
+                    alias: None,
+                },
+            ]
+        );
+        assert!(provenance[1].is_default());
+
+        // nothing is recorded outside `from_source_with_provenance`
+        crate::from_source::<Test, _>(&source)?;
+        assert!(super::RECORDER.take().is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "extrapolation")]
+    fn template() -> crate::Result {
+        #[derive(Debug, crate::Deserialize)]
+        #[envir(prefix = "APP_", expand)]
+        struct Test {
+            dir: String,
+            #[envir(default = "${APP_DIR}/cache")]
+            cache: String,
+            #[envir(secret)]
+            token: String,
+        }
+
+        let source = crate::Layered::new().fallback([
+            ("APP_DIR", "${HOME}/app"),
+            ("HOME", "/home/user"),
+            ("APP_TOKEN", "${HOME}"),
+        ]);
+        let (test, provenance) = crate::from_source_with_provenance::<Test, _>(&source)?;
+
+        assert_eq!(test.dir, "/home/user/app");
+        assert_eq!(test.cache, "/home/user/app/cache");
+        assert_eq!(test.token, "/home/user");
+        assert_eq!(provenance[0].template.as_deref(), Some("${HOME}/app"));
+        assert_eq!(
+            provenance[1].origin,
+            crate::Origin::Default {
+                value: "${APP_DIR}/cache".to_string()
+            }
+        );
+        assert_eq!(provenance[1].template.as_deref(), Some("${APP_DIR}/cache"));
+        assert!(provenance[1].is_expanded());
+        assert_eq!(provenance[2].template.as_deref(), Some(crate::REDACTED));
+
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "dotenv")]
+    fn file() -> crate::Result {
+        #[derive(Debug, crate::Deserialize)]
+        struct Test {
+            _provenance_file: String,
+        }
+
+        let dir = crate::testing::TempDir::new("provenance");
+        std::fs::write(dir.join(".env"), "# comment\n\n_PROVENANCE_FILE=value").unwrap();
+
+        let source = crate::Layered::new().dotenv(dir.join(".env"))?;
+        let (_, provenance) = crate::from_source_with_provenance::<Test, _>(&source)?;
+
+        assert_eq!(
+            provenance[0].origin,
+            crate::Origin::File {
+                path: dir.join(".env"),
+                line: Some(3),
+            }
+        );
+        assert_eq!(
+            provenance[0].origin.to_string(),
+            format!("{}:3", dir.join(".env").display())
+        );
+
+        Ok(())
+    }
+}
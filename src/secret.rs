@@ -0,0 +1,70 @@
+/**
+ * The text used in place of a secret value.
+ */
+pub const REDACTED: &str = "***";
+
+/**
+ * A wrapper around a secret value, like a password.
+ *
+ * Its value is redacted in `Debug` output and in parsing errors. Use `expose` to access the
+ * real value.
+ */
+#[derive(Clone, Default, Eq, PartialEq)]
+pub struct Secret<T>(T);
+
+impl<T> Secret<T> {
+    pub fn new(value: T) -> Self {
+        Self(value)
+    }
+
+    /**
+     * Returns a reference to the real value.
+     */
+    pub fn expose(&self) -> &T {
+        &self.0
+    }
+
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> From<T> for Secret<T> {
+    fn from(value: T) -> Self {
+        Self(value)
+    }
+}
+
+impl<T> std::fmt::Debug for Secret<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Secret").field(&REDACTED).finish()
+    }
+}
+
+impl<T: std::str::FromStr> std::str::FromStr for Secret<T> {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().map(Self).map_err(|_| REDACTED.to_string())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn debug() {
+        let secret = crate::Secret::new("password");
+
+        assert_eq!(format!("{secret:?}"), "Secret(\"***\")");
+        assert_eq!(secret.expose(), &"password");
+    }
+
+    #[test]
+    fn parse() {
+        let secret = "1".parse::<crate::Secret<u8>>();
+        assert_eq!(secret, Ok(crate::Secret::new(1)));
+
+        let secret = "password".parse::<crate::Secret<u8>>();
+        assert_eq!(secret, Err(crate::REDACTED.to_string()));
+    }
+}
@@ -2,6 +2,18 @@ pub use envir_derive::*;
 
 use std::collections::HashMap;
 
+/**
+ * How `Serialize::export_with_mode` deals with variables not collected.
+ */
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum ExportMode {
+    /** Keeps the current value of these variables. */
+    #[default]
+    Keep,
+    /** Unsets these variables, like a `None` field or a field skipped by `skip_export_if`. */
+    Unset,
+}
+
 pub trait Serialize {
     fn export(&self) {
         for (k, v) in self.collect() {
@@ -9,7 +21,97 @@ pub trait Serialize {
         }
     }
 
+    /**
+     * Likes `export` but with `ExportMode::Unset`, also unsets the variables listed by
+     * `export_vars` and not collected. Exporting the same value twice always produces the same
+     * environment.
+     */
+    fn export_with_mode(&self, mode: ExportMode)
+    where
+        Self: Sized,
+    {
+        let vars = self.collect();
+
+        if mode == ExportMode::Unset {
+            for pattern in Self::export_vars("") {
+                let current = match pattern.strip_suffix('*') {
+                    Some(prefix) => crate::Source::prefixed(&crate::ProcessEnv, prefix)
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|(k, _)| k)
+                        .collect(),
+                    None => vec![pattern],
+                };
+
+                for var in current {
+                    if !vars.contains_key(&var) {
+                        crate::remove(&var);
+                    }
+                }
+            }
+        }
+
+        for (k, v) in vars {
+            crate::set(&k, v);
+        }
+    }
+
     fn collect(&self) -> HashMap<String, String>;
+
+    /**
+     * Likes `collect` but adds `prefix` at the begining of variable names.
+     */
+    fn collect_prefixed(&self, prefix: &str) -> HashMap<String, String> {
+        self.collect()
+            .into_iter()
+            .map(|(k, v)| (format!("{prefix}{k}"), v))
+            .collect()
+    }
+
+    /**
+     * Likes `collect` but replaces the value of secret variables by `crate::REDACTED`.
+     */
+    fn collect_redacted(&self) -> HashMap<String, String>
+    where
+        Self: Sized,
+    {
+        let mut hash_map = self.collect();
+        let secrets = Self::secrets("");
+
+        for (var, value) in &mut hash_map {
+            if secrets.iter().any(|x| matches(x, var)) {
+                *value = crate::REDACTED.to_string();
+            }
+        }
+
+        hash_map
+    }
+
+    /**
+     * Lists the secret variables exported by this type, as name patterns (see `matches`) starting
+     * with `prefix`.
+     */
+    fn secrets(prefix: &str) -> Vec<String>
+    where
+        Self: Sized,
+    {
+        let _ = prefix;
+
+        Vec::new()
+    }
+
+    /**
+     * Lists the variables this type may export, as name patterns (see `matches`) starting with
+     * `prefix`.
+     */
+    fn export_vars(prefix: &str) -> Vec<String>
+    where
+        Self: Sized,
+    {
+        let _ = prefix;
+
+        Vec::new()
+    }
 }
 
 pub trait Deserialize {
@@ -17,14 +119,68 @@ pub trait Deserialize {
     where
         Self: Sized,
     {
-        let env = crate::collect();
-
-        Self::from(&env)
+        Self::from_source(&crate::ProcessEnv)
     }
 
     fn from(env: &HashMap<String, String>) -> crate::Result<Self>
     where
         Self: Sized;
+
+    /**
+     * Likes `from` but for any variables `source`.
+     */
+    fn from_source<S: crate::Source + ?Sized>(source: &S) -> crate::Result<Self>
+    where
+        Self: Sized,
+    {
+        Self::from_prefixed(source, "")
+    }
+
+    /**
+     * Likes `from_source` but adds `prefix` at the begining of variable names.
+     *
+     * The default implementation calls `from` with the variables of `source` starting with
+     * `prefix`, so it requires an iterable source.
+     */
+    fn from_prefixed<S: crate::Source + ?Sized>(source: &S, prefix: &str) -> crate::Result<Self>
+    where
+        Self: Sized,
+    {
+        let env = source
+            .prefixed(prefix)
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|(k, v)| Some((k.strip_prefix(prefix)?.to_string(), v)))
+            .collect();
+
+        Self::from(&env)
+    }
+
+    /**
+     * Lists the environment variables read by this type, as name patterns (see `matches`)
+     * starting with `prefix`.
+     */
+    fn vars(prefix: &str) -> Vec<String>
+    where
+        Self: Sized,
+    {
+        let _ = prefix;
+
+        Vec::new()
+    }
+
+    /**
+     * Describes the variables read by this type, for `crate::env_example`, `crate::markdown` and
+     * `crate::help`. Empty by default.
+     */
+    fn variables(prefix: &str) -> Vec<crate::Variable>
+    where
+        Self: Sized,
+    {
+        let _ = prefix;
+
+        Vec::new()
+    }
 }
 
 pub fn from_env<T>() -> crate::Result<T>
@@ -41,73 +197,541 @@ where
     T::from(env)
 }
 
-#[doc(hidden)]
-pub fn load_option<T: std::str::FromStr>(
-    env: &HashMap<String, String>,
-    var: &str,
-    default: Option<String>,
-    _separator: char,
-) -> crate::Result<Option<T>>
+pub fn from_source<T, S>(source: &S) -> crate::Result<T>
 where
-    T::Err: ToString,
+    T: Deserialize,
+    S: crate::Source + ?Sized,
 {
+    T::from_source(source)
+}
+
+/**
+ * Copies the variables of `source`, for the `load_with` functions. A non iterable source gives an
+ * empty map.
+ */
+#[doc(hidden)]
+pub fn materialize<S: crate::Source + ?Sized>(source: &S) -> HashMap<String, String> {
+    source
+        .prefixed("")
+        .unwrap_or_default()
+        .into_iter()
+        .collect()
+}
+
+/**
+ * Passed by the derive macro to enable the expansion of variable values. Only exists with the
+ * `extrapolation` feature.
+ */
+#[cfg(feature = "extrapolation")]
+#[doc(hidden)]
+pub const EXPAND: bool = true;
+
+/**
+ * The default value of a derived field. With the `extrapolation` feature, it’s a template parsed
+ * by the derive macro.
+ */
+#[doc(hidden)]
+pub struct DefaultValue {
+    value: &'static str,
     #[cfg(feature = "extrapolation")]
-    fn try_replace<'t, F: FnMut(&regex::Captures) -> crate::Result<String>>(
-        regex: &regex::Regex,
-        text: &'t str,
-        mut rep: F,
-    ) -> crate::Result<std::borrow::Cow<'t, str>> {
-        let mut it = regex.captures_iter(text).peekable();
+    template: crate::extrapolation::Template,
+}
 
-        if it.peek().is_none() {
-            return Ok(std::borrow::Cow::Borrowed(text));
-        }
+impl DefaultValue {
+    #[cfg(feature = "extrapolation")]
+    #[must_use]
+    pub const fn new(value: &'static str, template: crate::extrapolation::Template) -> Self {
+        Self { value, template }
+    }
 
-        let mut new = String::with_capacity(text.len());
-        let mut last_match = 0;
-        for cap in it {
-            // unwrap on 0 is OK because captures only reports matches
-            let m = cap.get(0).unwrap();
-            new.push_str(&text[last_match..m.start()]);
-            new.push_str(&rep(&cap)?);
-            last_match = m.end();
-        }
-        new.push_str(&text[last_match..]);
-        Ok(std::borrow::Cow::Owned(new))
+    #[cfg(not(feature = "extrapolation"))]
+    #[must_use]
+    pub const fn new(value: &'static str) -> Self {
+        Self { value }
     }
 
     #[cfg(feature = "extrapolation")]
-    let default = default
-        .map(|x| {
-            let regex = regex::Regex::new(r#"\$\{ *(?P<name>.*?) *\}"#).unwrap();
+    fn load<S: crate::Source + ?Sized>(
+        &self,
+        env: &S,
+        var: &str,
+    ) -> crate::Result<std::borrow::Cow<'static, str>> {
+        crate::extrapolation::expand_default(var, self.value, &self.template, &mut |name| {
+            lookup(env, name)
+        })
+        .map(Into::into)
+    }
+
+    #[cfg(not(feature = "extrapolation"))]
+    fn load<S: crate::Source + ?Sized>(
+        &self,
+        _: &S,
+        _: &str,
+    ) -> crate::Result<std::borrow::Cow<'static, str>> {
+        Ok(self.value.into())
+    }
+}
+
+/**
+ * Loads the variable `name`, which is `var` or one of its aliases (see `resolve_var`). The
+ * provenance is recorded under `var`.
+ */
+#[doc(hidden)]
+pub fn load_option<T: std::str::FromStr, S: crate::Source + ?Sized>(
+    env: &S,
+    var: &str,
+    name: &str,
+    default: Option<&DefaultValue>,
+    _separator: char,
+    expand: bool,
+) -> crate::Result<Option<T>>
+where
+    T::Err: ToString,
+{
+    let Some(value) = env.get(name)? else {
+        return default
+            .map(|x| {
+                let value = x.load(env, var)?;
+                crate::provenance::record(
+                    var,
+                    || crate::Origin::Default {
+                        value: x.value.to_string(),
+                    },
+                    (value != x.value).then_some(x.value),
+                    None,
+                );
 
-            try_replace(&regex, &x, |caps: &regex::Captures| {
-                crate::get(&caps["name"])
+                parse(var, &value)
             })
-            .map(|x| x.to_string())
-        })
-        .transpose()?;
+            .transpose()
+            .inspect(|x| record_unset(var, x.is_none()));
+    };
+
+    let expanded = expand_value(env, name, &value, expand)?;
+    crate::provenance::record(
+        var,
+        || env.origin(name),
+        (expanded != value).then_some(&value),
+        (name != var).then_some(name),
+    );
 
-    env.get(var)
-        .or(default.as_ref())
-        .map(|x| parse(var, x))
-        .transpose()
+    parse(name, &expanded).map(Some)
 }
 
+/**
+ * Likes `load_option` for a list. Values items are expanded after splitting, defaults are
+ * expanded before.
+ */
 #[doc(hidden)]
-pub fn load_vec<T: std::str::FromStr>(
-    env: &HashMap<String, String>,
+pub fn load_vec<T: std::str::FromStr, S: crate::Source + ?Sized>(
+    env: &S,
     var: &str,
-    default: Option<String>,
+    name: &str,
+    default: Option<&DefaultValue>,
     separator: char,
+    expand: bool,
 ) -> crate::Result<Option<Vec<T>>>
 where
     T::Err: ToString,
 {
-    env.get(var)
-        .or(default.as_ref())
-        .map(|x| x.split(separator).map(|x| parse(var, x)).collect())
-        .transpose()
+    let Some(value) = env.get(name)? else {
+        return default
+            .map(|x| {
+                let value = x.load(env, var)?;
+                crate::provenance::record(
+                    var,
+                    || crate::Origin::Default {
+                        value: x.value.to_string(),
+                    },
+                    (value != x.value).then_some(x.value),
+                    None,
+                );
+
+                value.split(separator).map(|x| parse(var, x)).collect()
+            })
+            .transpose()
+            .inspect(|x| record_unset(var, x.is_none()));
+    };
+
+    let items = value
+        .split(separator)
+        .map(|x| Ok((x, expand_value(env, name, x, expand)?)))
+        .collect::<crate::Result<Vec<_>>>()?;
+    let expanded = items.iter().any(|(raw, x)| raw != x);
+    crate::provenance::record(
+        var,
+        || env.origin(name),
+        expanded.then_some(&value),
+        (name != var).then_some(name),
+    );
+
+    items
+        .iter()
+        .map(|(_, x)| parse(name, x))
+        .collect::<crate::Result<_>>()
+        .map(Some)
+}
+
+/**
+ * Gathers the variables starting with `var` followed by `_`, keyed by the rest of their name
+ * converted per `case`. The variables in `claimed`, the other fields of the container, are left
+ * to them. A non iterable source gives an empty map.
+ */
+#[doc(hidden)]
+pub fn load_map<M, T, S>(
+    env: &S,
+    var: &str,
+    case: Option<crate::RenameRule>,
+    expand: bool,
+    claimed: &[String],
+) -> crate::Result<M>
+where
+    M: FromIterator<(String, T)>,
+    T: std::str::FromStr,
+    T::Err: ToString,
+    S: crate::Source + ?Sized,
+{
+    let prefix = format!("{var}_");
+    let pattern = format!("{prefix}*");
+    let mut vars = env.prefixed(&prefix).unwrap_or_default();
+    vars.sort();
+
+    vars.iter()
+        .filter(|(k, _)| {
+            // A sibling map claims its variables only if its prefix is the more specific one.
+            !claimed.iter().any(|x| {
+                *x != pattern && matches(x, k) && (!x.ends_with('*') || x.len() > pattern.len())
+            })
+        })
+        .filter_map(|(k, v)| Some((k, k.strip_prefix(&prefix)?, v)))
+        .filter(|(_, suffix, _)| !suffix.is_empty())
+        .map(|(k, suffix, v)| {
+            let expanded = expand_value(env, k, v, expand)?;
+            crate::provenance::record(
+                k,
+                || env.origin(k),
+                (expanded != v.as_str()).then_some(v),
+                None,
+            );
+
+            Ok((map_key(case, suffix), parse(k, &expanded)?))
+        })
+        .collect()
+}
+
+/**
+ * Converts the end of a variable name, `suffix`, to a map key.
+ */
+#[doc(hidden)]
+#[must_use]
+pub fn map_key(case: Option<crate::RenameRule>, suffix: &str) -> String {
+    case.map_or_else(|| suffix.to_string(), |x| x.apply(suffix))
+}
+
+/**
+ * Converts a map `key` back to the end of a variable name.
+ */
+#[doc(hidden)]
+#[must_use]
+pub fn map_suffix(case: Option<crate::RenameRule>, key: &str) -> String {
+    match case {
+        Some(_) => crate::RenameRule::ScreamingSnake.apply(key),
+        None => key.to_string(),
+    }
+}
+
+/**
+ * Checks if `var` matches the `pattern` listed by `Deserialize::vars`, `Serialize::export_vars`
+ * or `Serialize::secrets`.
+ *
+ * A pattern is a variable name, prefix included. A pattern ending with `*`, for a map field,
+ * stands for every variable starting with the rest of it.
+ */
+pub(crate) fn matches(pattern: &str, var: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => var.starts_with(prefix),
+        None => pattern == var,
+    }
+}
+
+fn record_unset(var: &str, unset: bool) {
+    if unset {
+        crate::provenance::record(var, || crate::Origin::Unset, None, None);
+    }
+}
+
+#[cfg(feature = "extrapolation")]
+fn expand_value<'a, S: crate::Source + ?Sized>(
+    env: &S,
+    var: &str,
+    value: &'a str,
+    expand: bool,
+) -> crate::Result<std::borrow::Cow<'a, str>> {
+    if !expand {
+        return Ok(value.into());
+    }
+
+    crate::extrapolation::expand(var, value, &mut |name| lookup(env, name)).map(Into::into)
+}
+
+#[cfg(feature = "extrapolation")]
+fn lookup<S: crate::Source + ?Sized>(env: &S, name: &str) -> crate::Result<Option<String>> {
+    env.get(name).map(|x| x.map(std::borrow::Cow::into_owned))
+}
+
+#[cfg(not(feature = "extrapolation"))]
+fn expand_value<'a, S: crate::Source + ?Sized>(
+    _: &S,
+    _: &str,
+    value: &'a str,
+    _: bool,
+) -> crate::Result<std::borrow::Cow<'a, str>> {
+    Ok(value.into())
+}
+
+#[doc(hidden)]
+pub fn check_field<T>(errors: &mut Vec<crate::Error>, result: crate::Result<T>) -> Option<T> {
+    match result {
+        Ok(value) => Some(value),
+        Err(error) => {
+            errors.push(error);
+            None
+        }
+    }
+}
+
+#[doc(hidden)]
+pub fn check_errors(errors: Vec<crate::Error>) -> crate::Result {
+    let mut errors = errors
+        .into_iter()
+        .flat_map(|x| match x {
+            crate::Error::Multiple(errors) => errors,
+            error => vec![error],
+        })
+        .collect::<Vec<_>>();
+
+    match errors.len() {
+        0 => Ok(()),
+        1 => Err(errors.remove(0)),
+        _ => Err(crate::Error::Multiple(errors)),
+    }
+}
+
+/**
+ * Returns the first set variable among `var`, its `aliases` and its `deprecated` names, or `var`
+ * if none is set. Using a deprecated name logs a warning.
+ */
+#[doc(hidden)]
+pub fn resolve_var<S: crate::Source + ?Sized>(
+    env: &S,
+    var: String,
+    aliases: &[String],
+    deprecated: &[String],
+) -> String {
+    if env.contains(&var) {
+        return var;
+    }
+
+    if let Some(alias) = aliases.iter().find(|x| env.contains(x)) {
+        return alias.clone();
+    }
+
+    if let Some(old) = deprecated.iter().find(|x| env.contains(x)) {
+        warn_deprecated(old, &var);
+        return old.clone();
+    }
+
+    var
+}
+
+#[cfg_attr(
+    not(any(feature = "logger", feature = "tracing")),
+    allow(unused_variables)
+)]
+fn warn_deprecated(old: &str, var: &str) {
+    #[cfg(feature = "logger")]
+    log::warn!("The '{old}' environment variable is deprecated, use '{var}' instead");
+
+    #[cfg(feature = "tracing")]
+    tracing::warn!("The '{old}' environment variable is deprecated, use '{var}' instead");
+}
+
+/**
+ * Returns an error for each variable of `env` starting with `prefix` not in `known`. Does nothing
+ * with an empty `prefix` or a non iterable source.
+ */
+#[doc(hidden)]
+pub fn check_unknown<S: crate::Source + ?Sized>(
+    env: &S,
+    prefix: &str,
+    known: &[String],
+) -> Vec<crate::Error> {
+    if prefix.is_empty() {
+        return Vec::new();
+    }
+
+    let mut unknown = env
+        .prefixed(prefix)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(k, _)| k)
+        .filter(|k| !known.iter().any(|x| matches(x, k)))
+        .collect::<Vec<_>>();
+    unknown.sort();
+
+    unknown
+        .iter()
+        .map(|key| {
+            let suggestion = known
+                .iter()
+                .filter(|x| !x.ends_with('*'))
+                .map(|x| (distance(key, x), x))
+                .filter(|(distance, _)| *distance <= key.len() / 3)
+                .min_by_key(|(distance, _)| *distance)
+                .map(|(_, x)| x.as_str());
+
+            crate::Error::unknown(key, suggestion)
+        })
+        .collect()
+}
+
+/**
+ * The Levenshtein distance between `a` and `b`.
+ */
+fn distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut row = (0..=b.len()).collect::<Vec<_>>();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut previous = row[0];
+        row[0] = i + 1;
+
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous + usize::from(ca != *cb);
+            previous = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(previous + 1);
+        }
+    }
+
+    row[b.len()]
+}
+
+#[doc(hidden)]
+pub fn load_nested_option<T: Deserialize, S: crate::Source + ?Sized>(
+    env: &S,
+    prefix: &str,
+) -> crate::Result<Option<T>> {
+    let vars = T::vars(prefix);
+
+    let is_set = |var: &String| match var.strip_suffix('*') {
+        Some(prefix) => env.prefixed(prefix).is_some_and(|x| !x.is_empty()),
+        None => env.contains(var),
+    };
+
+    if !vars.is_empty() && !vars.iter().any(is_set) {
+        return Ok(None);
+    }
+
+    T::from_prefixed(env, prefix).map(Some)
+}
+
+#[doc(hidden)]
+pub fn validate_with<T, E: ToString>(
+    var: &str,
+    value: &T,
+    validate: impl FnOnce(&T) -> Result<(), E>,
+) -> crate::Result {
+    validate(value).map_err(|e| crate::Error::validation(var, None, e))
+}
+
+#[doc(hidden)]
+pub fn validate_non_empty(var: &str, is_empty: bool) -> crate::Result {
+    if is_empty {
+        return Err(crate::Error::validation(var, None, "non_empty"));
+    }
+
+    Ok(())
+}
+
+#[doc(hidden)]
+pub fn validate_length(
+    var: &str,
+    len: usize,
+    min: Option<usize>,
+    max: Option<usize>,
+    rule: &str,
+) -> crate::Result {
+    if min.is_some_and(|min| len < min) || max.is_some_and(|max| len > max) {
+        return Err(crate::Error::validation(var, None, rule));
+    }
+
+    Ok(())
+}
+
+#[doc(hidden)]
+pub fn validate_range<T: PartialOrd + ToString>(
+    var: &str,
+    value: &T,
+    min: Option<T>,
+    max: Option<T>,
+    rule: &str,
+) -> crate::Result {
+    if min.is_some_and(|min| *value < min) || max.is_some_and(|max| *value > max) {
+        return Err(crate::Error::validation(var, Some(value.to_string()), rule));
+    }
+
+    Ok(())
+}
+
+/**
+ * The pattern of a `regex` attribute, checked by the derive macro and compiled once, at its first
+ * use.
+ */
+#[cfg(feature = "regex")]
+#[doc(hidden)]
+pub struct RegexValue {
+    pattern: &'static str,
+    regex: std::sync::OnceLock<Result<regex::Regex, regex::Error>>,
+}
+
+#[cfg(feature = "regex")]
+impl RegexValue {
+    #[must_use]
+    pub const fn new(pattern: &'static str) -> Self {
+        Self {
+            pattern,
+            regex: std::sync::OnceLock::new(),
+        }
+    }
+}
+
+#[cfg(feature = "regex")]
+#[doc(hidden)]
+pub fn validate_regex<T: AsRef<str>>(var: &str, value: &T, regex: &RegexValue) -> crate::Result {
+    let value = value.as_ref();
+    let re = regex
+        .regex
+        .get_or_init(|| regex::Regex::new(regex.pattern))
+        .as_ref()
+        .map_err(|e| crate::Error::validation(var, None, e))?;
+
+    if !re.is_match(value) {
+        return Err(crate::Error::validation(
+            var,
+            Some(value.to_string()),
+            format!("regex({})", regex.pattern),
+        ));
+    }
+
+    Ok(())
+}
+
+#[doc(hidden)]
+pub fn unknown_variant<T>(var: &str, value: &str, expected: &str) -> crate::Error {
+    crate::Error::parse::<T, _>(
+        var,
+        format!("unknown variant '{value}', expected one of: {expected}"),
+    )
 }
 
 fn parse<T: std::str::FromStr>(var: &str, value: &str) -> crate::Result<T>
@@ -146,10 +770,12 @@ mod test {
             Ok("field5".to_string())
         }
 
-        crate::set("ENV_FOO", "foo");
-        crate::set("ENV_FIELD4", 4);
-        crate::set("ENV_FIELD7", "value1,value2");
-        crate::set("ENV_FIELD8", "1;2");
+        let _guard = crate::testing::EnvGuard::new(&[
+            ("ENV_FOO", Some("foo")),
+            ("ENV_FIELD4", Some("4")),
+            ("ENV_FIELD7", Some("value1,value2")),
+            ("ENV_FIELD8", Some("1;2")),
+        ]);
 
         let test = crate::from_env::<Test>().unwrap();
         assert_eq!(
@@ -192,6 +818,14 @@ mod test {
             field5: None,
         };
 
+        let _guard = crate::testing::EnvGuard::new(&[
+            ("ENV2_FOO", None::<&str>),
+            ("ENV2_FIELD2", None),
+            ("ENV2_FIELD3", None),
+            ("ENV2_FIELD4", None),
+            ("ENV2_FIELD5", None),
+        ]);
+
         assert!(std::env::var("ENV2_FOO").is_err());
         assert!(std::env::var("ENV2_FIELD2").is_err());
         assert!(std::env::var("ENV2_FIELD3").is_err());
@@ -239,6 +873,7 @@ mod test {
 
         use crate::Serialize;
 
+        let _guard = crate::testing::EnvGuard::new(&[("ENV3_FOO", None::<&str>)]);
         assert!(std::env::var("ENV3_FOO").is_err());
         test.export();
         assert_eq!(std::env::var("ENV3_FOO"), Ok("foo".to_string()));
@@ -260,6 +895,49 @@ mod test {
         );
     }
 
+    #[test]
+    fn source() -> crate::Result {
+        #[derive(Debug, PartialEq, crate::Deserialize)]
+        #[envir(prefix = "APP_")]
+        struct Test {
+            host: String,
+            #[envir(default = "8080")]
+            port: u16,
+        }
+
+        struct Manual(String);
+
+        impl crate::Deserialize for Manual {
+            fn from(env: &std::collections::HashMap<String, String>) -> crate::Result<Self> {
+                env.get("HOST")
+                    .map(|x| Self(x.clone()))
+                    .ok_or_else(|| crate::Error::missing("HOST", None))
+            }
+        }
+
+        let expected = Test {
+            host: "localhost".to_string(),
+            port: 8080,
+        };
+
+        let source = crate::from_fn(|key| (key == "APP_HOST").then(|| "localhost".to_string()));
+        assert_eq!(crate::from_source::<Test, _>(&source)?, expected);
+
+        let btree_map =
+            std::collections::BTreeMap::from([("APP_HOST".to_string(), "localhost".to_string())]);
+        assert_eq!(crate::from_source::<Test, _>(&btree_map)?, expected);
+
+        let mut env = crate::Env::new();
+        env.set("APP_HOST", "localhost");
+        assert_eq!(crate::from_source::<Test, _>(&env)?, expected);
+        assert_eq!(
+            <Manual as crate::Deserialize>::from_prefixed(&env, "APP_")?.0,
+            "localhost"
+        );
+
+        Ok(())
+    }
+
     #[test]
     #[cfg(feature = "extrapolation")]
     fn extrapolation_error() {
@@ -272,6 +950,32 @@ mod test {
         assert!(crate::from_env::<Test>().is_err());
     }
 
+    #[test]
+    #[cfg(feature = "extrapolation")]
+    fn extrapolation_default() -> crate::Result {
+        #[derive(Debug, crate::Deserialize)]
+        struct Test {
+            #[envir(default = "${HOSTS:-localhost,${HOST}}")]
+            hosts: Vec<String>,
+        }
+
+        let mut env = std::collections::HashMap::new();
+        env.insert("HOST".to_string(), "example.org".to_string());
+
+        // the default template is parsed once, and expanded at each load
+        assert_eq!(
+            crate::from::<Test>(&env)?.hosts,
+            ["localhost", "example.org"]
+        );
+        env.insert("HOST".to_string(), "example.com".to_string());
+        assert_eq!(
+            crate::from::<Test>(&env)?.hosts,
+            ["localhost", "example.com"]
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn skip_export() {
         use crate::Serialize as _;
@@ -287,6 +991,8 @@ mod test {
             skip_export: "skip".to_string(),
         };
 
+        let _guard = crate::testing::EnvGuard::new(&[("SKIP_EXPORT", None::<&str>)]);
+
         test.export();
 
         assert!(std::env::var("SKIP_EXPORT").is_err());
@@ -321,6 +1027,7 @@ mod test {
 
         assert!(test.home.is_empty());
 
+        let _guard = crate::testing::EnvGuard::lock();
         test.export();
 
         assert!(!std::env::var("HOME").unwrap().is_empty());
@@ -342,10 +1049,757 @@ mod test {
             skip: "skip_if_empty".to_string(),
         };
 
+        let _guard = crate::testing::EnvGuard::new(&[("SKIP", None::<&str>)]);
+
         test.export();
 
         assert!(std::env::var("SKIP_IF_EMPTY").is_err());
 
         Ok(())
     }
+
+    #[test]
+    fn unit_enum() -> crate::Result {
+        use crate::Serialize as _;
+
+        #[derive(Debug, PartialEq, crate::Deserialize, crate::Serialize)]
+        enum Format {
+            Json,
+            #[envir(name = "human")]
+            Pretty,
+        }
+
+        #[derive(Debug, PartialEq, crate::Deserialize, crate::Serialize)]
+        #[envir(case_insensitive)]
+        enum Mode {
+            Dev,
+            Prod,
+        }
+
+        #[derive(Debug, PartialEq, crate::Deserialize, crate::Serialize)]
+        #[envir(prefix = "ENV5_")]
+        struct Test {
+            format: Format,
+            mode: Mode,
+        }
+
+        let mut env = std::collections::HashMap::new();
+        env.insert("ENV5_FORMAT".to_string(), "human".to_string());
+        env.insert("ENV5_MODE".to_string(), "PROD".to_string());
+
+        let test = crate::from::<Test>(&env)?;
+        assert_eq!(
+            test,
+            Test {
+                format: Format::Pretty,
+                mode: Mode::Prod,
+            }
+        );
+
+        let collected = test.collect();
+        assert_eq!(collected.get("ENV5_FORMAT"), Some(&"human".to_string()));
+        assert_eq!(collected.get("ENV5_MODE"), Some(&"prod".to_string()));
+
+        env.insert("ENV5_FORMAT".to_string(), "Json".to_string());
+        assert!(crate::from::<Test>(&env).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn tagged_enum() -> crate::Result {
+        use crate::Serialize as _;
+
+        #[derive(Debug, PartialEq, crate::Deserialize, crate::Serialize)]
+        #[envir(prefix = "ENV6_", tag = "STORAGE")]
+        enum Storage {
+            #[envir(prefix = "S3_")]
+            S3 {
+                bucket: String,
+                region: Option<String>,
+            },
+            #[envir(name = "fs", prefix = "FS_")]
+            Filesystem {
+                root: String,
+            },
+            Memory,
+        }
+
+        let mut env = std::collections::HashMap::new();
+        env.insert("ENV6_STORAGE".to_string(), "s3".to_string());
+        env.insert("ENV6_S3_BUCKET".to_string(), "bucket".to_string());
+
+        let test = crate::from::<Storage>(&env)?;
+        assert_eq!(
+            test,
+            Storage::S3 {
+                bucket: "bucket".to_string(),
+                region: None,
+            }
+        );
+        assert_eq!(test.collect(), env);
+
+        env.insert("ENV6_STORAGE".to_string(), "fs".to_string());
+        assert!(crate::from::<Storage>(&env).is_err());
+
+        env.insert("ENV6_FS_ROOT".to_string(), "/tmp".to_string());
+        assert_eq!(
+            crate::from::<Storage>(&env)?,
+            Storage::Filesystem {
+                root: "/tmp".to_string()
+            }
+        );
+
+        env.insert("ENV6_STORAGE".to_string(), "memory".to_string());
+        assert_eq!(crate::from::<Storage>(&env)?, Storage::Memory);
+
+        env.insert("ENV6_STORAGE".to_string(), "unknown".to_string());
+        assert!(crate::from::<Storage>(&env).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn nested_prefix() -> crate::Result {
+        use crate::Serialize as _;
+
+        #[derive(Debug, PartialEq, crate::Deserialize, crate::Serialize)]
+        #[envir(prefix = "ENV7_")]
+        struct Test {
+            #[envir(nested, prefix = "PRIMARY_")]
+            primary: Database,
+            #[envir(nested, prefix = "REPLICA_")]
+            replica: Database,
+        }
+
+        #[derive(Debug, PartialEq, crate::Deserialize, crate::Serialize)]
+        #[envir(prefix = "DB_")]
+        struct Database {
+            host: String,
+            #[envir(noprefix)]
+            port: u16,
+        }
+
+        let mut env = std::collections::HashMap::new();
+        env.insert("PRIMARY_DB_HOST".to_string(), "primary".to_string());
+        env.insert("PRIMARY_PORT".to_string(), "5432".to_string());
+        env.insert("REPLICA_DB_HOST".to_string(), "replica".to_string());
+        env.insert("REPLICA_PORT".to_string(), "5433".to_string());
+
+        let test = crate::from::<Test>(&env)?;
+        assert_eq!(
+            test,
+            Test {
+                primary: Database {
+                    host: "primary".to_string(),
+                    port: 5432,
+                },
+                replica: Database {
+                    host: "replica".to_string(),
+                    port: 5433,
+                },
+            }
+        );
+        assert_eq!(test.collect(), env);
+
+        Ok(())
+    }
+
+    #[test]
+    fn nested_option() -> crate::Result {
+        #[derive(Debug, PartialEq, crate::Deserialize)]
+        struct Test {
+            #[envir(nested)]
+            smtp: Option<Smtp>,
+        }
+
+        #[derive(Debug, PartialEq, crate::Deserialize)]
+        #[envir(prefix = "SMTP_")]
+        struct Smtp {
+            host: String,
+            port: u16,
+            user: Option<String>,
+        }
+
+        use crate::Deserialize as _;
+
+        assert_eq!(Test::vars(""), ["SMTP_HOST", "SMTP_PORT", "SMTP_USER"]);
+
+        let mut env = std::collections::HashMap::new();
+        assert_eq!(crate::from::<Test>(&env)?, Test { smtp: None });
+
+        env.insert("SMTP_HOST".to_string(), "localhost".to_string());
+        assert!(matches!(
+            crate::from::<Test>(&env),
+            Err(crate::Error::Missing(missing)) if missing.key() == "SMTP_PORT"
+        ));
+
+        env.insert("SMTP_PORT".to_string(), "25".to_string());
+        assert_eq!(
+            crate::from::<Test>(&env)?,
+            Test {
+                smtp: Some(Smtp {
+                    host: "localhost".to_string(),
+                    port: 25,
+                    user: None,
+                }),
+            }
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn multiple_errors() {
+        #[derive(Debug, crate::Deserialize)]
+        #[envir(prefix = "ENV8_")]
+        struct Test {
+            _field1: String,
+            _field2: u8,
+            #[envir(nested)]
+            _nested: Nested,
+        }
+
+        #[derive(Debug, crate::Deserialize)]
+        #[envir(prefix = "ENV8_NESTED_")]
+        struct Nested {
+            _field3: String,
+        }
+
+        let mut env = std::collections::HashMap::new();
+        env.insert("ENV8__FIELD2".to_string(), "-1".to_string());
+
+        let error = crate::from::<Test>(&env).unwrap_err();
+        let crate::Error::Multiple(ref errors) = error else {
+            panic!("{error:?}");
+        };
+
+        assert_eq!(errors.len(), 3);
+        assert!(
+            matches!(&errors[0], crate::Error::Missing(missing) if missing.key() == "ENV8__FIELD1")
+        );
+        assert!(matches!(&errors[1], crate::Error::Parse(_)));
+        assert!(
+            matches!(&errors[2], crate::Error::Missing(missing) if missing.key() == "ENV8_NESTED__FIELD3")
+        );
+        assert_eq!(
+            error.to_string(),
+            "3 configuration errors:
+  - Missing 'ENV8__FIELD1' environment variable
+  - Enable to parse 'ENV8__FIELD2' variable to 'u8': invalid digit found in string
+  - Missing 'ENV8_NESTED__FIELD3' environment variable"
+        );
+    }
+
+    #[test]
+    fn validation() {
+        #[derive(Debug, PartialEq, crate::Deserialize)]
+        #[envir(prefix = "ENV9_")]
+        struct Test {
+            #[envir(range(min = 1, max = 1024))]
+            port: u16,
+            #[envir(non_empty)]
+            name: String,
+            #[envir(length(max = 2), range(min = 1))]
+            workers: Vec<u8>,
+            #[envir(validate = "validate_even")]
+            even: Option<u8>,
+        }
+
+        fn validate_even(value: &Option<u8>) -> Result<(), String> {
+            match value {
+                Some(x) if x % 2 != 0 => Err(format!("{x} is not even")),
+                _ => Ok(()),
+            }
+        }
+
+        let mut env = std::collections::HashMap::new();
+        env.insert("ENV9_PORT".to_string(), "80".to_string());
+        env.insert("ENV9_NAME".to_string(), "name".to_string());
+        env.insert("ENV9_WORKERS".to_string(), "1,2".to_string());
+        env.insert("ENV9_EVEN".to_string(), "2".to_string());
+        assert!(crate::from::<Test>(&env).is_ok());
+
+        env.insert("ENV9_PORT".to_string(), "2048".to_string());
+        env.insert("ENV9_NAME".to_string(), String::new());
+        env.insert("ENV9_WORKERS".to_string(), "0,1,2".to_string());
+        env.insert("ENV9_EVEN".to_string(), "3".to_string());
+
+        let error = crate::from::<Test>(&env).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "4 configuration errors:
+  - Invalid value '2048' for 'ENV9_PORT' variable: range(1..=1024)
+  - Invalid value for 'ENV9_NAME' variable: non_empty
+  - Invalid value for 'ENV9_WORKERS' variable: length(..=2)
+  - Invalid value for 'ENV9_EVEN' variable: 3 is not even"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "regex")]
+    fn validation_regex() {
+        #[derive(Debug, crate::Deserialize)]
+        struct Test {
+            #[envir(regex = "^https?://")]
+            _url: String,
+        }
+
+        let mut env = std::collections::HashMap::new();
+        env.insert("_URL".to_string(), "https://example.org".to_string());
+        assert!(crate::from::<Test>(&env).is_ok());
+
+        env.insert("_URL".to_string(), "ftp://example.org".to_string());
+        assert_eq!(
+            crate::from::<Test>(&env).unwrap_err().to_string(),
+            "Invalid value 'ftp://example.org' for '_URL' variable: regex(^https?://)"
+        );
+    }
+
+    #[test]
+    fn secret() {
+        use crate::Serialize as _;
+
+        #[derive(Debug, crate::Deserialize, crate::Serialize)]
+        #[envir(prefix = "ENV10_")]
+        struct Test {
+            user: String,
+            password: crate::Secret<String>,
+            #[envir(secret, range(max = 9999))]
+            pin: u16,
+            #[envir(nested)]
+            nested: Option<Nested>,
+        }
+
+        #[derive(Debug, crate::Deserialize, crate::Serialize)]
+        struct Nested {
+            #[envir(name = "ENV10_TOKEN")]
+            token: Option<crate::Secret<u32>>,
+        }
+
+        let mut env = std::collections::HashMap::new();
+        env.insert("ENV10_USER".to_string(), "user".to_string());
+        env.insert("ENV10_PASSWORD".to_string(), "password".to_string());
+        env.insert("ENV10_PIN".to_string(), "12345".to_string());
+        env.insert("ENV10_TOKEN".to_string(), "token".to_string());
+
+        assert_eq!(
+            crate::from::<Test>(&env).unwrap_err().to_string(),
+            "2 configuration errors:
+  - Invalid value for 'ENV10_PIN' variable: range(..=9999)
+  - Enable to parse 'ENV10_TOKEN' variable to 'envir::secret::Secret<u32>': ***"
+        );
+
+        env.insert("ENV10_PIN".to_string(), "1234".to_string());
+        env.insert("ENV10_TOKEN".to_string(), "42".to_string());
+
+        let test = crate::from::<Test>(&env).unwrap();
+        assert_eq!(
+            format!("{test:?}"),
+            r#"Test { user: "user", password: Secret("***"), pin: 1234, nested: Some(Nested { token: Some(Secret("***")) }) }"#
+        );
+        assert_eq!(test.collect(), env);
+
+        let redacted = test.collect_redacted();
+        assert_eq!(redacted["ENV10_USER"], "user");
+        assert_eq!(redacted["ENV10_PASSWORD"], crate::REDACTED);
+        assert_eq!(redacted["ENV10_PIN"], crate::REDACTED);
+        assert_eq!(redacted["ENV10_TOKEN"], crate::REDACTED);
+    }
+
+    #[test]
+    #[cfg(feature = "extrapolation")]
+    fn secret_expand() {
+        #[derive(Debug, crate::Deserialize)]
+        struct Test {
+            #[envir(secret, expand)]
+            _password: String,
+            #[envir(secret, expand)]
+            _token: String,
+        }
+
+        let mut env = std::collections::HashMap::new();
+        env.insert("_PASSWORD".to_string(), "hunter2${".to_string());
+        env.insert("_TOKEN".to_string(), "${_KEY:?hunter2}".to_string());
+
+        assert_eq!(
+            crate::from::<Test>(&env).unwrap_err().to_string(),
+            "2 configuration errors:
+  - Invalid template '***' for '_PASSWORD' variable, at position 9: ***
+  - Unable to expand '_TOKEN' variable: _KEY: ***"
+        );
+    }
+
+    #[test]
+    fn help() {
+        #[derive(Debug, crate::Deserialize)]
+        struct Test {
+            /// PostgreSQL connection string
+            _database_url: String,
+            #[envir(help = "Listening port")]
+            _port: u16,
+        }
+
+        let error = crate::from::<Test>(&std::collections::HashMap::new()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "2 configuration errors:
+  - Missing '_DATABASE_URL' environment variable: PostgreSQL connection string
+  - Missing '_PORT' environment variable: Listening port"
+        );
+    }
+
+    #[test]
+    fn export_unset() {
+        use crate::Serialize as _;
+
+        #[derive(crate::Serialize)]
+        #[envir(prefix = "ENV11_")]
+        struct Test {
+            field1: Option<String>,
+            #[envir(skip_export_if = "String::is_empty")]
+            field2: String,
+            #[envir(nested)]
+            nested: Option<Nested>,
+        }
+
+        #[derive(crate::Serialize)]
+        #[envir(prefix = "ENV11_NESTED_")]
+        struct Nested {
+            field3: String,
+        }
+
+        let _guard = crate::testing::EnvGuard::new(&[
+            ("ENV11_FIELD1", None::<&str>),
+            ("ENV11_FIELD2", None),
+            ("ENV11_NESTED_FIELD3", None),
+        ]);
+
+        let test = Test {
+            field1: Some("field1".to_string()),
+            field2: "field2".to_string(),
+            nested: Some(Nested {
+                field3: "field3".to_string(),
+            }),
+        };
+        test.export();
+
+        let test = Test {
+            field1: None,
+            field2: String::new(),
+            nested: None,
+        };
+
+        test.export_with_mode(crate::ExportMode::Keep);
+        assert!(std::env::var("ENV11_FIELD1").is_ok());
+        assert!(std::env::var("ENV11_FIELD2").is_ok());
+        assert!(std::env::var("ENV11_NESTED_FIELD3").is_ok());
+
+        test.export_with_mode(crate::ExportMode::Unset);
+        assert!(std::env::var("ENV11_FIELD1").is_err());
+        assert!(std::env::var("ENV11_FIELD2").is_err());
+        assert!(std::env::var("ENV11_NESTED_FIELD3").is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "extrapolation")]
+    fn expand() -> crate::Result {
+        #[derive(Debug, PartialEq, crate::Deserialize)]
+        struct Test {
+            #[envir(expand)]
+            data_dir: String,
+            #[envir(expand, separator = ':')]
+            paths: Vec<String>,
+            raw: String,
+        }
+
+        #[derive(Debug, PartialEq, crate::Deserialize)]
+        #[envir(expand)]
+        struct Test2 {
+            raw: String,
+        }
+
+        let mut env = std::collections::HashMap::new();
+        env.insert("HOME".to_string(), "/home/user".to_string());
+        env.insert("DATA_DIR".to_string(), "${HOME}/data".to_string());
+        env.insert("PATHS".to_string(), "$HOME/bin:/usr/bin".to_string());
+        env.insert("RAW".to_string(), "${HOME}".to_string());
+
+        assert_eq!(
+            crate::from::<Test>(&env)?,
+            Test {
+                data_dir: "/home/user/data".to_string(),
+                paths: vec!["/home/user/bin".to_string(), "/usr/bin".to_string()],
+                raw: "${HOME}".to_string(),
+            }
+        );
+        assert_eq!(
+            crate::from::<Test2>(&env)?,
+            Test2 {
+                raw: "/home/user".to_string(),
+            }
+        );
+
+        env.insert("HOME".to_string(), "${RAW}".to_string());
+        assert_eq!(
+            crate::from::<Test2>(&env).unwrap_err().to_string(),
+            "Unable to expand 'RAW' variable: cycle detected RAW -> HOME -> RAW"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn deny_unknown() {
+        #[derive(Debug, crate::Deserialize)]
+        struct Database {
+            #[envir(default = "localhost")]
+            _url: String,
+        }
+
+        #[derive(Debug, crate::Deserialize)]
+        #[envir(prefix = "APP_", deny_unknown)]
+        struct Test {
+            #[envir(default = "8080")]
+            _port: u16,
+            #[envir(nested, prefix = "APP_DATABASE_")]
+            _database: Database,
+            #[envir(load_with = "load_extra", load_vars = "EXTRA")]
+            _extra: String,
+        }
+
+        fn load_extra(env: &std::collections::HashMap<String, String>) -> crate::Result<String> {
+            Ok(env.get("APP_EXTRA").cloned().unwrap_or_default())
+        }
+
+        let mut env = std::collections::HashMap::new();
+        env.insert("APP__PORT".to_string(), "80".to_string());
+        env.insert("APP_EXTRA".to_string(), "extra".to_string());
+        env.insert("APP_DATABASE__URL".to_string(), "postgres://".to_string());
+        env.insert("OTHER".to_string(), "value".to_string());
+        assert!(crate::from::<Test>(&env).is_ok());
+
+        env.insert("APP_DATABSE__URL".to_string(), "postgres://".to_string());
+        env.insert("APP_UNKNOWN".to_string(), "value".to_string());
+        assert_eq!(
+            crate::from::<Test>(&env).unwrap_err().to_string(),
+            "2 configuration errors:
+  - Unknown 'APP_DATABSE__URL' environment variable, did you mean 'APP_DATABASE__URL'?
+  - Unknown 'APP_UNKNOWN' environment variable"
+        );
+    }
+
+    #[test]
+    fn deny_unknown_unit_variant() -> crate::Result {
+        #[derive(Debug, PartialEq, crate::Deserialize)]
+        #[envir(prefix = "APP_", tag = "STORAGE", deny_unknown)]
+        enum Storage {
+            #[envir(prefix = "S3_")]
+            S3 {
+                bucket: String,
+            },
+            Memory,
+        }
+
+        let mut env = std::collections::HashMap::new();
+        env.insert("APP_STORAGE".to_string(), "memory".to_string());
+        env.insert("APP_S3_BUCKET".to_string(), "bucket".to_string());
+        assert_eq!(crate::from::<Storage>(&env)?, Storage::Memory);
+
+        env.insert("APP_S3_BUCKTE".to_string(), "bucket".to_string());
+        assert_eq!(
+            crate::from::<Storage>(&env).unwrap_err().to_string(),
+            "Unknown 'APP_S3_BUCKTE' environment variable, did you mean 'APP_S3_BUCKET'?"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn alias() -> crate::Result {
+        use crate::Serialize as _;
+
+        #[derive(Debug, PartialEq, crate::Deserialize, crate::Serialize)]
+        #[envir(prefix = "APP_")]
+        struct Test {
+            #[envir(alias = "DB_URL", alias = "DSN", deprecated = "DATABASE")]
+            database_url: String,
+            #[envir(deprecated = "SIZE", export_aliases)]
+            pool_size: u8,
+        }
+
+        let mut env = std::collections::HashMap::new();
+        env.insert("APP_DSN".to_string(), "postgres://dsn".to_string());
+        env.insert("APP_DATABASE".to_string(), "postgres://old".to_string());
+        env.insert("APP_SIZE".to_string(), "5".to_string());
+
+        let test = crate::from::<Test>(&env)?;
+        assert_eq!(
+            test,
+            Test {
+                database_url: "postgres://dsn".to_string(),
+                pool_size: 5,
+            }
+        );
+
+        env.insert("APP_DATABASE_URL".to_string(), "postgres://new".to_string());
+        assert_eq!(crate::from::<Test>(&env)?.database_url, "postgres://new");
+
+        let collected = test.collect();
+        assert_eq!(collected.get("APP_POOL_SIZE"), Some(&"5".to_string()));
+        assert_eq!(collected.get("APP_SIZE"), Some(&"5".to_string()));
+        assert_eq!(collected.get("APP_DSN"), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn rename_all() -> crate::Result {
+        use crate::Serialize as _;
+
+        #[derive(Debug, PartialEq, crate::Deserialize, crate::Serialize)]
+        #[envir(prefix = "APP_", rename_all = "SCREAMING_SNAKE_CASE")]
+        #[allow(non_snake_case)]
+        struct Test {
+            dbHost: String,
+            r#type: String,
+            httpURLPrefix: String,
+        }
+
+        #[derive(Debug, PartialEq, crate::Deserialize, crate::Serialize)]
+        #[envir(rename_all = "kebab-case")]
+        enum Mode {
+            DryRun,
+            Production,
+        }
+
+        #[derive(Debug, PartialEq, crate::Deserialize)]
+        struct Test2 {
+            r#type: Mode,
+        }
+
+        let mut env = std::collections::HashMap::new();
+        env.insert("APP_DB_HOST".to_string(), "localhost".to_string());
+        env.insert("APP_TYPE".to_string(), "main".to_string());
+        env.insert("APP_HTTP_URL_PREFIX".to_string(), "/api".to_string());
+        env.insert("TYPE".to_string(), "dry-run".to_string());
+
+        let test = crate::from::<Test>(&env)?;
+        assert_eq!(test.dbHost, "localhost");
+        assert_eq!(test.r#type, "main");
+        assert_eq!(test.httpURLPrefix, "/api");
+
+        let mut collected = test.collect().into_keys().collect::<Vec<_>>();
+        collected.sort();
+        assert_eq!(
+            collected,
+            ["APP_DB_HOST", "APP_HTTP_URL_PREFIX", "APP_TYPE"]
+        );
+
+        assert_eq!(crate::from::<Test2>(&env)?.r#type, Mode::DryRun);
+        assert_eq!(Mode::Production.to_string(), "production");
+
+        Ok(())
+    }
+
+    #[test]
+    fn map_sibling() -> crate::Result {
+        #[derive(crate::Deserialize)]
+        #[envir(prefix = "APP_")]
+        struct Test {
+            feature: std::collections::HashMap<String, bool>,
+            feature_flag: bool,
+            feature_limit: std::collections::HashMap<String, u32>,
+        }
+
+        let env = std::collections::HashMap::from([
+            ("APP_FEATURE_BETA".to_string(), "true".to_string()),
+            ("APP_FEATURE_FLAG".to_string(), "true".to_string()),
+            ("APP_FEATURE_LIMIT_CPU".to_string(), "2".to_string()),
+        ]);
+
+        let test = crate::from::<Test>(&env)?;
+        assert_eq!(
+            test.feature,
+            std::collections::HashMap::from([("BETA".to_string(), true)])
+        );
+        assert!(test.feature_flag);
+        assert_eq!(
+            test.feature_limit,
+            std::collections::HashMap::from([("CPU".to_string(), 2)])
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn map() -> crate::Result {
+        use crate::{Deserialize as _, Serialize as _};
+
+        #[derive(Debug, PartialEq, crate::Deserialize, crate::Serialize)]
+        #[envir(prefix = "APP_", deny_unknown)]
+        struct Test {
+            #[envir(key_case = "kebab-case")]
+            feature: std::collections::HashMap<String, bool>,
+            #[envir(name = "LIMIT")]
+            limits: std::collections::BTreeMap<String, u32>,
+            #[envir(secret)]
+            token: std::collections::HashMap<String, String>,
+        }
+
+        let mut env = std::collections::HashMap::new();
+        env.insert("APP_FEATURE_DARK_MODE".to_string(), "true".to_string());
+        env.insert("APP_FEATURE_BETA".to_string(), "false".to_string());
+        env.insert("APP_FEATURES".to_string(), "true".to_string());
+        env.insert("APP_LIMIT_Cpu".to_string(), "2".to_string());
+        env.insert("APP_TOKEN_GITHUB".to_string(), "hunter2".to_string());
+
+        let test = crate::from::<Test>(&env);
+        assert_eq!(
+            test.unwrap_err().to_string(),
+            "Unknown 'APP_FEATURES' environment variable"
+        );
+
+        env.remove("APP_FEATURES");
+        let test = crate::from::<Test>(&env)?;
+        assert_eq!(
+            test.feature,
+            std::collections::HashMap::from([
+                ("dark-mode".to_string(), true),
+                ("beta".to_string(), false),
+            ])
+        );
+        assert_eq!(
+            test.limits,
+            std::collections::BTreeMap::from([("Cpu".to_string(), 2)])
+        );
+
+        assert_eq!(test.collect(), env);
+        assert_eq!(
+            test.collect_redacted()
+                .get("APP_TOKEN_GITHUB")
+                .map(String::as_str),
+            Some(crate::REDACTED)
+        );
+        assert_eq!(
+            Test::vars(""),
+            ["APP_FEATURE_*", "APP_LIMIT_*", "APP_TOKEN_*"]
+        );
+
+        env.insert("APP_LIMIT_MEMORY".to_string(), "lots".to_string());
+        assert_eq!(
+            crate::from::<Test>(&env).unwrap_err().to_string(),
+            "Enable to parse 'APP_LIMIT_MEMORY' variable to 'u32': invalid digit found in string"
+        );
+
+        let camel = Some(crate::RenameRule::Camel);
+        assert_eq!(super::map_key(camel, "DARK_MODE"), "darkMode");
+        assert_eq!(super::map_suffix(camel, "darkMode"), "DARK_MODE");
+        assert_eq!(
+            super::map_suffix(Some(crate::RenameRule::Pascal), "DarkMode2"),
+            "DARK_MODE2"
+        );
+
+        Ok(())
+    }
 }
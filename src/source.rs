@@ -0,0 +1,245 @@
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+
+/**
+ * A set of variables to load a configuration from.
+ */
+pub trait Source {
+    /**
+     * Gets the variable `key`. This returns a `crate::Error::Unicode` if the variable value
+     * isn’t valid unicode.
+     */
+    fn get(&self, key: &str) -> crate::Result<Option<Cow<'_, str>>>;
+
+    /**
+     * Checks if the variable `key` is set.
+     */
+    fn contains(&self, key: &str) -> bool {
+        !matches!(self.get(key), Ok(None))
+    }
+
+    /**
+     * Lists the variables whose name starts with `prefix`, if this source can be iterated.
+     * Variables with a non unicode value are ignored.
+     */
+    fn prefixed(&self, prefix: &str) -> Option<Vec<(String, String)>> {
+        let _ = prefix;
+
+        None
+    }
+
+    /**
+     * Describes where the variable `key` comes from.
+     */
+    fn origin(&self, key: &str) -> Origin {
+        let _ = key;
+
+        Origin::Source
+    }
+}
+
+/**
+ * Where the value of a variable comes from.
+ */
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Origin {
+    /** The process environment. */
+    Process,
+    /** A *.env* file, at this line (starting at 1) if known. */
+    File {
+        path: std::path::PathBuf,
+        line: Option<usize>,
+    },
+    /** Any other source, like a map. */
+    Source,
+    /** The `default` attribute of the field, with its raw value. */
+    Default { value: String },
+    /** The variable isn’t set. */
+    Unset,
+}
+
+impl std::fmt::Display for Origin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Process => f.write_str("process environment"),
+            Self::File { path, line: None } => write!(f, "{}", path.display()),
+            Self::File {
+                path,
+                line: Some(line),
+            } => write!(f, "{}:{line}", path.display()),
+            Self::Source => f.write_str("source"),
+            Self::Default { .. } => f.write_str("default"),
+            Self::Unset => f.write_str("unset"),
+        }
+    }
+}
+
+/**
+ * The process environment, read on demand.
+ */
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ProcessEnv;
+
+impl Source for ProcessEnv {
+    fn get(&self, key: &str) -> crate::Result<Option<Cow<'_, str>>> {
+        crate::try_get(key).map(|x| x.map(Cow::Owned))
+    }
+
+    fn contains(&self, key: &str) -> bool {
+        std::env::var_os(key).is_some()
+    }
+
+    fn origin(&self, _: &str) -> Origin {
+        Origin::Process
+    }
+
+    fn prefixed(&self, prefix: &str) -> Option<Vec<(String, String)>> {
+        let vars = std::env::vars_os()
+            .filter_map(|(k, v)| Some((k.into_string().ok()?, v.into_string().ok()?)))
+            .filter(|(k, _)| k.starts_with(prefix))
+            .collect();
+
+        Some(vars)
+    }
+}
+
+impl<H: std::hash::BuildHasher> Source for HashMap<String, String, H> {
+    fn get(&self, key: &str) -> crate::Result<Option<Cow<'_, str>>> {
+        Ok(HashMap::get(self, key).map(|x| Cow::Borrowed(x.as_str())))
+    }
+
+    fn prefixed(&self, prefix: &str) -> Option<Vec<(String, String)>> {
+        let vars = self
+            .iter()
+            .filter(|(k, _)| k.starts_with(prefix))
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect();
+
+        Some(vars)
+    }
+}
+
+impl Source for BTreeMap<String, String> {
+    fn get(&self, key: &str) -> crate::Result<Option<Cow<'_, str>>> {
+        Ok(BTreeMap::get(self, key).map(|x| Cow::Borrowed(x.as_str())))
+    }
+
+    fn prefixed(&self, prefix: &str) -> Option<Vec<(String, String)>> {
+        let vars = self
+            .range::<str, _>((
+                std::ops::Bound::Included(prefix),
+                std::ops::Bound::Unbounded,
+            ))
+            .take_while(|(k, _)| k.starts_with(prefix))
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect();
+
+        Some(vars)
+    }
+}
+
+impl Source for crate::Env {
+    fn get(&self, key: &str) -> crate::Result<Option<Cow<'_, str>>> {
+        self.try_get(key).map(|x| x.map(Cow::Owned))
+    }
+
+    fn contains(&self, key: &str) -> bool {
+        crate::Env::contains(self, key)
+    }
+
+    fn prefixed(&self, prefix: &str) -> Option<Vec<(String, String)>> {
+        let vars = self
+            .iter()
+            .filter(|(k, _)| k.starts_with(prefix))
+            .filter_map(|(k, v)| Some((k.to_string(), v.to_str()?.to_string())))
+            .collect();
+
+        Some(vars)
+    }
+}
+
+/**
+ * A source calling a function for each variable, created by `from_fn`.
+ */
+#[derive(Clone, Copy, Debug)]
+pub struct FromFn<F>(F);
+
+impl<F: Fn(&str) -> Option<String>> Source for FromFn<F> {
+    fn get(&self, key: &str) -> crate::Result<Option<Cow<'_, str>>> {
+        Ok((self.0)(key).map(Cow::Owned))
+    }
+}
+
+/**
+ * Creates a source from the `f` function, which returns the value of a variable. This source
+ * can’t be iterated.
+ */
+pub fn from_fn<F: Fn(&str) -> Option<String>>(f: F) -> FromFn<F> {
+    FromFn(f)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::Source as _;
+
+    #[test]
+    fn maps() -> crate::Result {
+        let hash_map = std::collections::HashMap::from([
+            ("APP_A".to_string(), "a".to_string()),
+            ("APP_B".to_string(), "b".to_string()),
+            ("OTHER".to_string(), "other".to_string()),
+        ]);
+        let btree_map = hash_map
+            .clone()
+            .into_iter()
+            .collect::<std::collections::BTreeMap<_, _>>();
+        let env = hash_map.clone().into_iter().collect::<crate::Env>();
+
+        for source in [&hash_map as &dyn crate::Source, &btree_map, &env] {
+            assert_eq!(source.get("APP_A")?.as_deref(), Some("a"));
+            assert_eq!(source.get("MISSING")?, None);
+            assert!(source.contains("OTHER"));
+
+            let mut vars = source.prefixed("APP_").unwrap();
+            vars.sort();
+            assert_eq!(
+                vars,
+                [
+                    ("APP_A".to_string(), "a".to_string()),
+                    ("APP_B".to_string(), "b".to_string()),
+                ]
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn process() -> crate::Result {
+        let _guard = crate::testing::EnvGuard::new(&[("SOURCE_PROCESS", Some("value"))]);
+
+        assert_eq!(
+            crate::ProcessEnv.get("SOURCE_PROCESS")?.as_deref(),
+            Some("value")
+        );
+        assert!(
+            crate::ProcessEnv
+                .prefixed("SOURCE_")
+                .unwrap()
+                .contains(&("SOURCE_PROCESS".to_string(), "value".to_string()))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn from_fn() -> crate::Result {
+        let source = crate::from_fn(|key| (key == "KEY").then(|| "value".to_string()));
+
+        assert_eq!(source.get("KEY")?.as_deref(), Some("value"));
+        assert!(!source.contains("MISSING"));
+        assert!(source.prefixed("").is_none());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,346 @@
+/*!
+ * Helpers to safely modify the process environment in tests.
+ *
+ * The process environment is shared by all the tests of a binary, running in parallel. The
+ * `EnvGuard` serializes the modifications with a process-wide lock and restores the previous
+ * values when dropped.
+ *
+ * ```
+ * envir::testing::with_env(&[("APP_PORT", Some("8080")), ("APP_DEBUG", None)], || {
+ *     assert_eq!(envir::get("APP_PORT").unwrap(), "8080");
+ *     assert!(envir::try_get("APP_DEBUG").unwrap().is_none());
+ * });
+ * ```
+ */
+
+use std::ffi::{OsStr, OsString};
+
+/**
+ * Who holds the process-wide lock.
+ */
+enum Owner {
+    /** `EnvGuard`s of this thread, `depth` of them are alive. */
+    Thread {
+        id: std::thread::ThreadId,
+        depth: usize,
+    },
+    /** A `with_env_async` future, which can’t be nested. */
+    Task,
+}
+
+struct State {
+    owner: Option<Owner>,
+    /** The `with_env_async` futures waiting for the lock. */
+    wakers: Vec<std::task::Waker>,
+}
+
+static STATE: std::sync::Mutex<State> = std::sync::Mutex::new(State {
+    owner: None,
+    wakers: Vec::new(),
+});
+static RELEASED: std::sync::Condvar = std::sync::Condvar::new();
+
+fn state() -> std::sync::MutexGuard<'static, State> {
+    STATE
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+/**
+ * Holds the process-wide lock and restores the modified variables on drop.
+ *
+ * Guards can be nested in the same thread, the lock is only acquired by the outermost one. They
+ * can’t be created inside `with_env_async`, that would wait for the future to complete.
+ */
+pub struct EnvGuard {
+    previous: Vec<(OsString, Option<OsString>)>,
+}
+
+impl EnvGuard {
+    /**
+     * Acquires the lock, without modifying any variable.
+     */
+    #[must_use]
+    pub fn lock() -> Self {
+        let id = std::thread::current().id();
+        let mut state = state();
+
+        loop {
+            match &mut state.owner {
+                None => {
+                    state.owner = Some(Owner::Thread { id, depth: 1 });
+                    break;
+                }
+                Some(Owner::Thread { id: owner, depth }) if *owner == id => {
+                    *depth += 1;
+                    break;
+                }
+                Some(_) => {
+                    state = RELEASED
+                        .wait(state)
+                        .unwrap_or_else(std::sync::PoisonError::into_inner);
+                }
+            }
+        }
+
+        Self {
+            previous: Vec::new(),
+        }
+    }
+
+    /**
+     * Likes `lock` but waits for the lock without blocking the thread. The lock isn’t tied to
+     * the thread, so the guard can be held across `.await`.
+     */
+    async fn lock_async() -> Self {
+        std::future::poll_fn(|cx| {
+            let mut state = state();
+
+            if state.owner.is_none() {
+                state.owner = Some(Owner::Task);
+                std::task::Poll::Ready(())
+            } else {
+                state.wakers.push(cx.waker().clone());
+                std::task::Poll::Pending
+            }
+        })
+        .await;
+
+        Self {
+            previous: Vec::new(),
+        }
+    }
+
+    fn set_all<K: AsRef<OsStr>, V: AsRef<OsStr>>(&mut self, vars: &[(K, Option<V>)]) {
+        for (key, value) in vars {
+            match value {
+                Some(value) => self.set(key, value),
+                None => self.remove(key),
+            }
+        }
+    }
+
+    /**
+     * Acquires the lock then sets (or unsets for `None`) the variables.
+     */
+    #[must_use]
+    pub fn new<K: AsRef<OsStr>, V: AsRef<OsStr>>(vars: &[(K, Option<V>)]) -> Self {
+        let mut guard = Self::lock();
+        guard.set_all(vars);
+
+        guard
+    }
+
+    /**
+     * Sets the variable `key` to `value`, its previous value is restored on drop.
+     */
+    pub fn set<K: AsRef<OsStr>, V: AsRef<OsStr>>(&mut self, key: K, value: V) {
+        self.save(key.as_ref());
+
+        unsafe {
+            std::env::set_var(key, value);
+        }
+    }
+
+    /**
+     * Unsets the variable `key`, its previous value is restored on drop.
+     */
+    pub fn remove<K: AsRef<OsStr>>(&mut self, key: K) {
+        self.save(key.as_ref());
+
+        unsafe {
+            std::env::remove_var(key);
+        }
+    }
+
+    fn save(&mut self, key: &OsStr) {
+        if !self.previous.iter().any(|(k, _)| k == key) {
+            self.previous
+                .push((key.to_os_string(), std::env::var_os(key)));
+        }
+    }
+}
+
+impl Drop for EnvGuard {
+    fn drop(&mut self) {
+        for (key, value) in self.previous.drain(..).rev() {
+            unsafe {
+                match value {
+                    Some(value) => std::env::set_var(key, value),
+                    None => std::env::remove_var(key),
+                }
+            }
+        }
+
+        let mut state = state();
+
+        match &mut state.owner {
+            Some(Owner::Thread { depth, .. }) if *depth > 1 => *depth -= 1,
+            _ => {
+                state.owner = None;
+                state.wakers.drain(..).for_each(std::task::Waker::wake);
+                RELEASED.notify_all();
+            }
+        }
+    }
+}
+
+/**
+ * Runs `f` with the variables set (or unset for `None`), then restores their previous values.
+ */
+pub fn with_env<K, V, F, R>(vars: &[(K, Option<V>)], f: F) -> R
+where
+    K: AsRef<OsStr>,
+    V: AsRef<OsStr>,
+    F: FnOnce() -> R,
+{
+    let _guard = EnvGuard::new(vars);
+
+    f()
+}
+
+/**
+ * Likes `with_env` for asynchronous code: the variables are restored after the future
+ * completes.
+ *
+ * The lock is awaited without blocking the thread and the returned future is `Send` if `f` and
+ * its future are, so other tasks keep running meanwhile.
+ */
+pub async fn with_env_async<K, V, F, Fut>(vars: &[(K, Option<V>)], f: F) -> Fut::Output
+where
+    K: AsRef<OsStr>,
+    V: AsRef<OsStr>,
+    F: FnOnce() -> Fut,
+    Fut: std::future::Future,
+{
+    let mut guard = EnvGuard::lock_async().await;
+    guard.set_all(vars);
+
+    f().await
+}
+
+/**
+ * A directory for the tests of this crate, removed with its content on drop, even if the test
+ * fails.
+ */
+#[cfg(all(test, feature = "dotenv"))]
+pub(crate) struct TempDir(std::path::PathBuf);
+
+#[cfg(all(test, feature = "dotenv"))]
+impl TempDir {
+    pub fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("envir-{name}-{}", std::process::id()));
+        std::fs::create_dir_all(&path).unwrap();
+
+        Self(path)
+    }
+}
+
+#[cfg(all(test, feature = "dotenv"))]
+impl std::ops::Deref for TempDir {
+    type Target = std::path::Path;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[cfg(all(test, feature = "dotenv"))]
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        std::fs::remove_dir_all(&self.0).ok();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn guard() {
+        {
+            let mut guard = super::EnvGuard::new(&[("TESTING_SET", Some("1"))]);
+            guard.remove("TESTING_SET");
+            assert!(std::env::var("TESTING_SET").is_err());
+
+            guard.set("TESTING_SET", "2");
+            assert_eq!(std::env::var("TESTING_SET"), Ok("2".to_string()));
+        }
+
+        assert!(std::env::var("TESTING_SET").is_err());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn nested() {
+        use std::os::unix::ffi::OsStrExt as _;
+
+        let value = std::ffi::OsStr::from_bytes(&[0xff]);
+
+        super::with_env(&[("TESTING_NESTED", Some(value))], || {
+            super::with_env(&[("TESTING_NESTED", Some("1"))], || {
+                assert_eq!(std::env::var("TESTING_NESTED"), Ok("1".to_string()));
+            });
+
+            assert_eq!(std::env::var_os("TESTING_NESTED").as_deref(), Some(value));
+        });
+
+        assert!(std::env::var_os("TESTING_NESTED").is_none());
+    }
+
+    #[test]
+    fn interleaved_tasks() {
+        use std::future::Future as _;
+
+        fn assert_send<T: Send>(_: &T) {}
+
+        /**
+         * Returns `Pending` once, to let the other task run.
+         */
+        async fn yield_now() {
+            let mut yielded = false;
+
+            std::future::poll_fn(|cx| {
+                if yielded {
+                    std::task::Poll::Ready(())
+                } else {
+                    yielded = true;
+                    cx.waker().wake_by_ref();
+                    std::task::Poll::Pending
+                }
+            })
+            .await;
+        }
+
+        async fn task(value: &str) {
+            let vars = [("TESTING_ASYNC", Some(value))];
+
+            super::with_env_async(&vars, || async {
+                yield_now().await;
+                assert_eq!(std::env::var("TESTING_ASYNC"), Ok(value.to_string()));
+                yield_now().await;
+                assert_eq!(std::env::var("TESTING_ASYNC"), Ok(value.to_string()));
+            })
+            .await;
+        }
+
+        let mut a = std::pin::pin!(task("a"));
+        let mut b = std::pin::pin!(task("b"));
+        assert_send(&a);
+
+        let mut cx = std::task::Context::from_waker(std::task::Waker::noop());
+        let mut done = (false, false);
+
+        // both tasks run on this thread, each one checks its value isn’t changed by the other
+        while done != (true, true) {
+            if !done.0 {
+                done.0 = a.as_mut().poll(&mut cx).is_ready();
+            }
+            if !done.1 {
+                done.1 = b.as_mut().poll(&mut cx).is_ready();
+            }
+
+            std::thread::yield_now();
+        }
+
+        assert!(std::env::var_os("TESTING_ASYNC").is_none());
+    }
+}